@@ -0,0 +1,89 @@
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Error as SynError, Ident, Path, Token, parenthesized, parse::Parse};
+
+pub struct ErrorBridge {
+    source_node: Path,
+    source_variants: Vec<BridgedVariant>,
+    target_node: Path,
+}
+
+/// A variant of the source node: `[(name) =] (error)`, declared as in the node.
+struct BridgedVariant {
+    /// The name of the variant, required when the variant is named in the source node.
+    ident: Option<Ident>,
+    path: Path,
+}
+
+impl Parse for BridgedVariant {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let ident = if input.peek(Ident) && input.peek2(Token![=]) {
+            let ident: Ident = input.parse()?;
+            let _: Token![=] = input.parse()?;
+            Some(ident)
+        } else {
+            None
+        };
+        Ok(BridgedVariant {
+            ident,
+            path: input.parse()?,
+        })
+    }
+}
+
+impl Parse for ErrorBridge {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        Self::parse_keyword(input, "from")?;
+        let source_node: Path = input.parse()?;
+        let content;
+        parenthesized!(content in input);
+        let source_variants = content
+            .parse_terminated(BridgedVariant::parse, Token![,])?
+            .into_iter()
+            .collect();
+        Self::parse_keyword(input, "into")?;
+        let target_node: Path = input.parse()?;
+        Ok(ErrorBridge {
+            source_node,
+            source_variants,
+            target_node,
+        })
+    }
+}
+
+impl ErrorBridge {
+    fn parse_keyword(input: syn::parse::ParseStream, keyword: &str) -> syn::Result<()> {
+        let ident: Ident = input.parse()?;
+        if ident == keyword {
+            Ok(())
+        } else {
+            Err(SynError::new(ident.span(), format!("expected '{}'", keyword)))
+        }
+    }
+
+    pub fn to_token_stream(&self) -> TokenStream {
+        let source_node = &self.source_node;
+        let target_node = &self.target_node;
+        // The unnamed variants are named after their position, as in the source node.
+        let variant_matches = self.source_variants.iter().enumerate().map(|(index, variant)| {
+            let variant_name = match &variant.ident {
+                Some(ident) => ident.clone(),
+                None => format_ident!("Variant{}", index),
+            };
+            let variant_type = &variant.path;
+            quote! {
+                #source_node::#variant_name(err, ..) => <#target_node as From<#variant_type>>::from(err),
+            }
+        });
+        quote! {
+            impl From<#source_node> for #target_node {
+                fn from(value: #source_node) -> Self {
+                    match value {
+                        #( #variant_matches )*
+                    }
+                }
+            }
+        }
+        .into()
+    }
+}
@@ -0,0 +1,158 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{
+    Attribute, Ident, ItemStruct, LitStr, Path, Token, Visibility, braced, parenthesized, parse::Parse, parse_quote,
+    token,
+};
+
+use crate::{
+    error_leaf::{ErrorLeaf, ErrorLeafConfig},
+    error_node::ErrorNode,
+};
+
+/// A whole tree of errors, declared as its root node: `[visibility] (node)`.
+pub struct ErrorHierarchy {
+    /// The visibility of the leaves and the nodes of the tree.
+    visibility: Visibility,
+    root: HierarchyNode,
+}
+
+/// A node of the tree: `(name) [= (prefix)] { (children) }`.
+struct HierarchyNode {
+    attributes: Vec<Attribute>,
+    name: Ident,
+    prefix: Option<LitStr>,
+    children: Vec<HierarchyChild>,
+}
+
+/// A child of a node of the tree, which becomes a variant of the node.
+enum HierarchyChild {
+    /// A nested node.
+    Node(HierarchyNode),
+    /// A leaf declared in the tree, with a constant message: `(name)("message")`.
+    Leaf { attributes: Vec<Attribute>, name: Ident, message: LitStr },
+    /// An error declared elsewhere, wrapped by a variant with the given name: `(name)(error)`.
+    Named { name: Ident, path: Path },
+    /// An error declared elsewhere, wrapped by a variant named after the last segment of its path.
+    Existing(Path),
+}
+
+impl Parse for ErrorHierarchy {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let attributes = input.call(Attribute::parse_outer)?;
+        let visibility: Visibility = input.parse()?;
+        let name: Ident = input.parse()?;
+        let root = HierarchyNode::parse_after_name(input, attributes, name)?;
+        Ok(ErrorHierarchy { visibility, root })
+    }
+}
+
+impl HierarchyNode {
+    /// Parses the prefix and the children of a node whose attributes and name are already parsed.
+    fn parse_after_name(input: syn::parse::ParseStream, attributes: Vec<Attribute>, name: Ident) -> syn::Result<Self> {
+        let prefix = if input.peek(Token![=]) {
+            let _: Token![=] = input.parse()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        let content;
+        braced!(content in input);
+        let children = content
+            .parse_terminated(HierarchyChild::parse, Token![,])?
+            .into_iter()
+            .collect();
+        Ok(HierarchyNode {
+            attributes,
+            name,
+            prefix,
+            children,
+        })
+    }
+}
+
+impl Parse for HierarchyChild {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let attributes = input.call(Attribute::parse_outer)?;
+        let is_declared = input.peek(Ident) && (input.peek2(Token![=]) || input.peek2(token::Brace) || input.peek2(token::Paren));
+        if !is_declared {
+            if let Some(attribute) = attributes.first() {
+                return Err(syn::Error::new_spanned(attribute, "an error declared elsewhere cannot have attributes"));
+            }
+            return Ok(HierarchyChild::Existing(input.parse()?));
+        }
+        let name: Ident = input.parse()?;
+        if !input.peek(token::Paren) {
+            return Ok(HierarchyChild::Node(HierarchyNode::parse_after_name(input, attributes, name)?));
+        }
+        let content;
+        parenthesized!(content in input);
+        if content.peek(LitStr) {
+            Ok(HierarchyChild::Leaf {
+                attributes,
+                name,
+                message: content.parse()?,
+            })
+        } else if let Some(attribute) = attributes.first() {
+            Err(syn::Error::new_spanned(attribute, "an error declared elsewhere cannot have attributes"))
+        } else {
+            Ok(HierarchyChild::Named {
+                name,
+                path: content.parse()?,
+            })
+        }
+    }
+}
+
+impl ErrorHierarchy {
+    pub fn to_token_stream(&self) -> TokenStream {
+        let mut token_buffer = TokenStream::new();
+        if let Err(e) = self.node_tokens(&self.root, &mut token_buffer) {
+            return e.to_compile_error().into();
+        }
+        token_buffer
+    }
+
+    /// Generates the given node after the leaves and the nodes it wraps, which are generated
+    /// recursively.
+    fn node_tokens(&self, node: &HierarchyNode, token_buffer: &mut TokenStream) -> syn::Result<()> {
+        let vis = &self.visibility;
+        let mut variants = vec![];
+        for child in &node.children {
+            match child {
+                HierarchyChild::Node(child_node) => {
+                    self.node_tokens(child_node, token_buffer)?;
+                    let child_name = &child_node.name;
+                    variants.push(quote! { #child_name = #child_name });
+                }
+                HierarchyChild::Leaf {
+                    attributes,
+                    name,
+                    message,
+                } => {
+                    let config: ErrorLeafConfig = parse_quote! { message = #message };
+                    let struct_def: ItemStruct = parse_quote! {
+                        #( #attributes )*
+                        #vis struct #name {}
+                    };
+                    token_buffer.extend(ErrorLeaf::new(config, struct_def)?.to_token_stream());
+                    variants.push(quote! { #name = #name });
+                }
+                HierarchyChild::Named { name, path } => variants.push(quote! { #name = #path }),
+                HierarchyChild::Existing(path) => {
+                    let name = &path.segments.last().expect("a path has at least one segment").ident;
+                    variants.push(quote! { #name = #path });
+                }
+            }
+        }
+        let attributes = &node.attributes;
+        let name = &node.name;
+        let prefix = node.prefix.as_ref().map(|prefix| quote! { = #prefix });
+        let error_node: ErrorNode = syn::parse2(quote! {
+            #( #attributes )*
+            #vis type #name<#( #variants ),*> #prefix
+        })?;
+        token_buffer.extend(error_node.to_token_stream());
+        Ok(())
+    }
+}
@@ -0,0 +1,1702 @@
+use std::{error::Error, fmt::Display, str::FromStr};
+
+use proc_macro::TokenStream;
+use proc_macro2::{Span, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::{
+    Error as SynError, Expr, ExprClosure, ExprMacro, Field, Fields, GenericArgument, Generics, Ident, Index, ItemEnum, ItemStruct, LitBool, LitInt, LitStr, Macro, Member, PathArguments, Token, Type, TypeParamBound, Visibility, parenthesized, parse::Parse, parse::Parser,
+    parse_quote,
+};
+
+use crate::error_node::manual_debug_assertion;
+
+/// Returns the name of the hidden field storing the message of `precompute` leaves.
+fn precomputed_message_field() -> Ident {
+    Ident::new("precomputed_message", Span::call_site())
+}
+
+/// Returns the name of the hidden field storing the creation time of `timestamp` leaves.
+fn timestamp_field() -> Ident {
+    Ident::new("created_at", Span::call_site())
+}
+
+/// The message of a struct leaf.
+#[derive(Clone)]
+enum LeafMessage {
+    /// A `format!` call, evaluated on each display.
+    Format(Macro),
+    /// A constant `&str` expression, evaluated at compile time.
+    Const(Expr),
+    /// The only field of a `self_message` or `transparent` leaf.
+    Field(Member),
+    /// The singular and the plural format strings of a `plural` leaf, chosen by the count.
+    Plural { count: Expr, singular: LitStr, plural: LitStr },
+    /// A closure taking the leaf and the formatter, used as the body of [std::fmt::Display::fmt].
+    Fmt(ExprClosure),
+    /// The arguments of a format macro, whose string can refer to the named fields of the struct,
+    /// which are listed once the struct is known.
+    Interpolated { args: TokenStream2, fields: Vec<Ident> },
+}
+
+impl LeafMessage {
+    /// Returns the keyword which gave the message, to report its conflicts with other keywords.
+    fn keyword(&self) -> ErrorLeafConfigKeyword {
+        match self {
+            LeafMessage::Plural { .. } => ErrorLeafConfigKeyword::Plural,
+            LeafMessage::Fmt(_) => ErrorLeafConfigKeyword::Fmt,
+            _ => ErrorLeafConfigKeyword::Message,
+        }
+    }
+
+    /// Returns the arguments of a format macro with the given format string of a `plural` leaf,
+    /// passing the count only if the format string has a placeholder for it.
+    fn plural_args(format: &LitStr) -> TokenStream2 {
+        if format.value().replace("{{", "").contains('{') {
+            quote! { #format, count }
+        } else {
+            quote! { #format }
+        }
+    }
+
+    /// Returns the statement binding `SELF_TYPE` to the name of the struct, if the format macro
+    /// refers to it.
+    fn self_type_binding(format_arg: &Macro, struct_name: &Ident) -> TokenStream2 {
+        if !format_arg.tokens.to_string().contains("SELF_TYPE") {
+            return TokenStream2::new();
+        }
+        quote! {
+            #[allow(non_snake_case)]
+            let SELF_TYPE = stringify!(#struct_name);
+        }
+    }
+
+    /// Returns the statement writing the message in [std::fmt::Display::fmt].
+    fn write_tokens(&self, struct_name: &Ident) -> TokenStream2 {
+        match self {
+            LeafMessage::Format(format_arg) => {
+                let self_type_binding = Self::self_type_binding(format_arg, struct_name);
+                quote! {
+                    #self_type_binding
+                    write!(f, "{}", #format_arg)
+                }
+            }
+            // An inline const block, unlike a const item, can refer to `Self`.
+            LeafMessage::Const(message) => quote! {
+                f.write_str(const { #message })
+            },
+            LeafMessage::Field(member) => quote! { std::fmt::Display::fmt(&self.#member, f) },
+            LeafMessage::Plural { count, singular, plural } => {
+                let singular_args = Self::plural_args(singular);
+                let plural_args = Self::plural_args(plural);
+                quote! {
+                    let count = &#count;
+                    if *count == 1 {
+                        write!(f, #singular_args)
+                    } else {
+                        write!(f, #plural_args)
+                    }
+                }
+            }
+            LeafMessage::Fmt(closure) => quote! {
+                let fmt: fn(&Self, &mut std::fmt::Formatter<'_>) -> std::fmt::Result = #closure;
+                fmt(self, f)
+            },
+            LeafMessage::Interpolated { args, fields } => {
+                // The formatter is bound with a hygienic name, as the fields may shadow `f`.
+                let formatter = Ident::new("formatter", Span::mixed_site());
+                quote! {
+                    let #formatter = f;
+                    #[allow(unused_variables)]
+                    let Self { #( #fields, )* .. } = self;
+                    write!(#formatter, #args)
+                }
+            }
+        }
+    }
+
+    /// Returns the expression computing the message as a [String].
+    fn string_tokens(&self, struct_name: &Ident) -> TokenStream2 {
+        match self {
+            LeafMessage::Format(format_arg) => {
+                let self_type_binding = Self::self_type_binding(format_arg, struct_name);
+                quote! {
+                    #self_type_binding
+                    #format_arg
+                }
+            }
+            LeafMessage::Const(message) => quote! {
+                String::from(const { #message })
+            },
+            LeafMessage::Field(member) => quote! { self.#member.to_string() },
+            LeafMessage::Plural { count, singular, plural } => {
+                let singular_args = Self::plural_args(singular);
+                let plural_args = Self::plural_args(plural);
+                quote! {
+                    let count = &#count;
+                    if *count == 1 {
+                        format!(#singular_args)
+                    } else {
+                        format!(#plural_args)
+                    }
+                }
+            }
+            LeafMessage::Fmt(closure) => quote! {
+                struct Message<'a, T>(&'a T, fn(&T, &mut std::fmt::Formatter<'_>) -> std::fmt::Result);
+
+                impl<T> std::fmt::Display for Message<'_, T> {
+                    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        (self.1)(self.0, f)
+                    }
+                }
+
+                Message(self, #closure).to_string()
+            },
+            LeafMessage::Interpolated { args, fields } => quote! {
+                #[allow(unused_variables)]
+                let Self { #( #fields, )* .. } = self;
+                format!(#args)
+            },
+        }
+    }
+}
+
+/// Generates the `to_log_line` method of a leaf, which has a kind only if it is declared with
+/// `kind_str`, and a code only if it is declared with `code`.
+fn log_line_method(vis: &Visibility, has_kind_str: bool, has_code: bool) -> TokenStream2 {
+    let kind = if has_kind_str { quote! { Self::KIND } } else { quote! { "" } };
+    let code = if has_code { quote! { Self::CODE } } else { quote! { "" } };
+    quote! {
+        /// Returns a log line with the kind, the code and the message of this error, separated by
+        /// `|`. The kind and the code are empty if they are not declared.
+        #vis fn to_log_line(&self) -> String {
+            format!("{}|{}|{}", #kind, #code, self)
+        }
+    }
+}
+
+/// Generates the `CODE` constant and the `code` method of a leaf declared with `code`, and the
+/// `as_parts` method of every leaf.
+fn code_methods(vis: &Visibility, code: Option<&LitInt>) -> TokenStream2 {
+    let Some(code) = code else {
+        return quote! {
+            /// Returns the code of this error, which is always 0 as it has no code, and its message.
+            #vis fn as_parts(&self) -> (u32, String) {
+                (0, self.to_string())
+            }
+        };
+    };
+    quote! {
+        /// The numeric code of this error.
+        #vis const CODE: u32 = #code;
+
+        /// Returns the numeric code of this error.
+        #vis fn code(&self) -> u32 {
+            Self::CODE
+        }
+
+        /// Returns the code and the message of this error.
+        #vis fn as_parts(&self) -> (u32, String) {
+            (Self::CODE, self.to_string())
+        }
+    }
+}
+
+/// Generates the implementation of [`hierrorchy::Coded`] of a leaf declared with `code`, which is
+/// used by the nodes wrapping it.
+fn coded_impl(name: &Ident, generics: &Generics, code: Option<&LitInt>) -> TokenStream2 {
+    if code.is_none() {
+        return TokenStream2::new();
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        impl #impl_generics ::hierrorchy::Coded for #name #ty_generics #where_clause {
+            fn code(&self) -> u32 {
+                Self::CODE
+            }
+        }
+    }
+}
+
+/// Generates the `source` method of the `Error` implementation of a leaf declared with `source_fn`,
+/// which returns the value of the given expression.
+fn source_method(source_fn: Option<&Expr>) -> TokenStream2 {
+    match source_fn {
+        Some(source_fn) => quote! {
+            fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                #source_fn
+            }
+        },
+        None => TokenStream2::new(),
+    }
+}
+
+/// Generates the `provide` method of the `Error` implementation of a leaf with a source, which
+/// forwards the requests to the source. It is emitted only when `hierrorchy` is built with
+/// `--cfg hierrorchy_provide`, as `Error::provide` is unstable.
+fn provide_method(source_fn: Option<&Expr>) -> TokenStream2 {
+    match source_fn {
+        Some(source_fn) => quote! {
+            ::hierrorchy::__private::with_provide! {
+                fn provide<'a>(&'a self, request: &mut std::error::Request<'a>) {
+                    let source: Option<&(dyn std::error::Error + 'static)> = #source_fn;
+                    if let Some(source) = source {
+                        source.provide(request);
+                    }
+                }
+            }
+        },
+        None => TokenStream2::new(),
+    }
+}
+
+/// Generates the conversion of a leaf declared with `into_io` into an [std::io::Error] of the given
+/// kind, carrying the message of the leaf.
+fn into_io_impl(name: &Ident, generics: &Generics, kind: Option<&Ident>) -> TokenStream2 {
+    let Some(kind) = kind else {
+        return TokenStream2::new();
+    };
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        impl #impl_generics From<#name #ty_generics> for std::io::Error #where_clause {
+            fn from(err: #name #ty_generics) -> Self {
+                std::io::Error::new(std::io::ErrorKind::#kind, err.to_string())
+            }
+        }
+    }
+}
+
+/// Generates the implementation of [`hierrorchy::Retryable`] of a leaf declared with `retryable`,
+/// which is used by the nodes wrapping it.
+fn retryable_impl(name: &Ident, generics: &Generics, retryable: bool) -> TokenStream2 {
+    if !retryable {
+        return TokenStream2::new();
+    }
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        impl #impl_generics ::hierrorchy::Retryable for #name #ty_generics #where_clause {
+            fn is_retryable(&self) -> bool {
+                true
+            }
+        }
+    }
+}
+
+/// Generates the `is_retryable` method of a leaf declared with `retryable`.
+fn retryable_method(vis: &Visibility) -> TokenStream2 {
+    quote! {
+        /// Returns whether the operation which failed with this error can be retried.
+        #vis fn is_retryable(&self) -> bool {
+            true
+        }
+    }
+}
+
+/// Generates the `severity` and `is_at_least` methods of a leaf, which use the declared `severity`
+/// or [`Severity::Error`](hierrorchy::Severity::Error) without one.
+fn severity_methods(vis: &Visibility, severity: Option<&Ident>) -> syn::Result<TokenStream2> {
+    let severity = match severity {
+        Some(severity) if !["Warn", "Error", "Fatal"].iter().any(|it| severity == it) => {
+            return Err(SynError::new(
+                severity.span(),
+                format!("unknown severity '{}', expected one of Warn, Error or Fatal", severity),
+            ));
+        }
+        Some(severity) => severity.clone(),
+        None => format_ident!("Error"),
+    };
+    Ok(quote! {
+        /// Returns the severity of this error.
+        #vis fn severity(&self) -> ::hierrorchy::Severity {
+            ::hierrorchy::Severity::#severity
+        }
+
+        /// Returns whether the severity of this error is at least the given one.
+        #vis fn is_at_least(&self, severity: ::hierrorchy::Severity) -> bool {
+            self.severity() >= severity
+        }
+    })
+}
+
+/// Generates the implementation of [`hierrorchy::HasSeverity`] of a leaf, which is used by the
+/// nodes wrapping it.
+fn has_severity_impl(name: &Ident, generics: &Generics) -> TokenStream2 {
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    quote! {
+        impl #impl_generics ::hierrorchy::HasSeverity for #name #ty_generics #where_clause {
+            fn severity(&self) -> ::hierrorchy::Severity {
+                #name::severity(self)
+            }
+        }
+    }
+}
+
+pub struct ErrorLeaf {
+    config: ErrorLeafConfig,
+    struct_def: ItemStruct,
+}
+
+impl ErrorLeaf {
+    pub fn new(config: ErrorLeafConfig, struct_def: ItemStruct) -> syn::Result<ErrorLeaf> {
+        let leaf = ErrorLeaf { config, struct_def };
+        let leaf = leaf.take_self_message()?;
+        let leaf = leaf.take_source_field()?;
+        let leaf = leaf.take_transparent()?;
+        #[cfg(feature = "thiserror_compat")]
+        let leaf = leaf.take_error_attribute()?;
+        let leaf = leaf.take_interpolated_fields()?;
+        leaf.check_clone_message_only()?;
+        Ok(leaf)
+    }
+
+    /// Checks that `clone_message_only` is used with `precompute`, which stores the message cloned
+    /// in place of the fields, and not with another `Clone` implementation.
+    fn check_clone_message_only(&self) -> syn::Result<()> {
+        if !self.config.clone_message_only {
+            return Ok(());
+        }
+        let message = if !self.config.precompute {
+            format!(
+                "keyword {} requires keyword {}",
+                ErrorLeafConfigKeyword::CloneMessageOnly,
+                ErrorLeafConfigKeyword::Precompute
+            )
+        } else if self.config.clone_via_string {
+            format!(
+                "keyword {} cannot be used with keyword {}",
+                ErrorLeafConfigKeyword::CloneMessageOnly,
+                ErrorLeafConfigKeyword::CloneViaString
+            )
+        } else {
+            return Ok(());
+        };
+        Err(SynError::new(self.struct_def.ident.span(), message))
+    }
+
+    /// Sets the only field of the struct as the message of `self_message` leaves.
+    fn take_self_message(mut self) -> syn::Result<Self> {
+        if !self.config.self_message {
+            return Ok(self);
+        }
+        let conflicting_keyword = if let Some(message) = &self.config.message {
+            Some(message.keyword())
+        } else if self.config.precompute {
+            Some(ErrorLeafConfigKeyword::Precompute)
+        } else if self.config.timestamp {
+            Some(ErrorLeafConfigKeyword::Timestamp)
+        } else {
+            None
+        };
+        if let Some(keyword) = conflicting_keyword {
+            return Err(SynError::new(
+                self.struct_def.ident.span(),
+                format!("keyword {} cannot be used with keyword {}", keyword, ErrorLeafConfigKeyword::SelfMessage),
+            ));
+        }
+        let member = self.only_field(ErrorLeafConfigKeyword::SelfMessage)?;
+        self.config.message = Some(LeafMessage::Field(member));
+        Ok(self)
+    }
+
+    /// Sets the field given with the `source` keyword, or marked with a `#[source]` attribute (which
+    /// is removed from the struct), as the source of the leaf.
+    fn take_source_field(mut self) -> syn::Result<Self> {
+        let members: Vec<Member> = self.struct_def.fields.members().collect();
+        let mut marked_members = vec![];
+        for (member, field) in members.iter().zip(self.struct_def.fields.iter_mut()) {
+            if let Some(position) = field.attrs.iter().position(|it| it.path().is_ident("source")) {
+                let attribute = field.attrs.remove(position);
+                attribute.meta.require_path_only()?;
+                marked_members.push((member.clone(), attribute));
+            }
+        }
+        let member = match (self.config.source.take(), marked_members.pop()) {
+            (None, None) => return Ok(self),
+            (Some(_), Some((_, attribute))) => {
+                return Err(SynError::new_spanned(
+                    attribute,
+                    format!("the source cannot be given with both #[source] and keyword {}", ErrorLeafConfigKeyword::Source),
+                ))
+            }
+            (None, Some((_, attribute))) if !marked_members.is_empty() => {
+                return Err(SynError::new_spanned(attribute, "only one field can be marked with #[source]"));
+            }
+            (Some(member), None) | (None, Some((member, _))) => member,
+        };
+        if !members.contains(&member) {
+            return Err(SynError::new_spanned(&member, "the struct has no such field"));
+        }
+        let conflicting_keyword = if self.config.source_fn.is_some() {
+            Some(ErrorLeafConfigKeyword::SourceFn)
+        } else if self.config.transparent {
+            Some(ErrorLeafConfigKeyword::Transparent)
+        } else {
+            None
+        };
+        if let Some(keyword) = conflicting_keyword {
+            return Err(SynError::new(
+                self.struct_def.ident.span(),
+                format!("the source of the leaf cannot be given with keyword {}", keyword),
+            ));
+        }
+        self.config.source_fn = Some(self.field_source(&member));
+        Ok(self)
+    }
+
+    /// Returns the expression returning the given field as the source of the leaf. A boxed error is
+    /// unboxed, as `Box<dyn Error>` does not implement `Error` itself.
+    fn field_source(&self, member: &Member) -> Expr {
+        let is_boxed = self
+            .struct_def
+            .fields
+            .members()
+            .zip(self.struct_def.fields.iter())
+            .any(|(it, field)| &it == member && Self::is_boxed_error(&field.ty));
+        if is_boxed {
+            parse_quote! { Some(&*self.#member) }
+        } else {
+            parse_quote! { Some(&self.#member) }
+        }
+    }
+
+    /// Sets the only field of the struct as both the message and the source of `transparent`
+    /// leaves.
+    fn take_transparent(mut self) -> syn::Result<Self> {
+        if !self.config.transparent {
+            return Ok(self);
+        }
+        let conflicting_keyword = if self.config.self_message {
+            Some(ErrorLeafConfigKeyword::SelfMessage)
+        } else if let Some(message) = &self.config.message {
+            Some(message.keyword())
+        } else if self.config.source_fn.is_some() {
+            Some(ErrorLeafConfigKeyword::SourceFn)
+        } else if self.config.precompute {
+            Some(ErrorLeafConfigKeyword::Precompute)
+        } else if self.config.timestamp {
+            Some(ErrorLeafConfigKeyword::Timestamp)
+        } else {
+            None
+        };
+        if let Some(keyword) = conflicting_keyword {
+            return Err(SynError::new(
+                self.struct_def.ident.span(),
+                format!("keyword {} cannot be used with keyword {}", keyword, ErrorLeafConfigKeyword::Transparent),
+            ));
+        }
+        let member = self.only_field(ErrorLeafConfigKeyword::Transparent)?;
+        self.config.source_fn = Some(self.field_source(&member));
+        self.config.message = Some(LeafMessage::Field(member));
+        Ok(self)
+    }
+
+    /// Returns the only field of the struct, required by the given keyword.
+    fn only_field(&self, keyword: ErrorLeafConfigKeyword) -> syn::Result<Member> {
+        let mut fields = self.struct_def.fields.iter();
+        let (Some(field), None) = (fields.next(), fields.next()) else {
+            return Err(SynError::new(
+                self.struct_def.ident.span(),
+                format!("keyword {} requires a struct with exactly one field", keyword),
+            ));
+        };
+        Ok(match &field.ident {
+            Some(name) => Member::Named(name.clone()),
+            None => Member::Unnamed(Index::from(0)),
+        })
+    }
+
+    /// Generates the conversions from strings of `self_message` leaves.
+    fn self_message_conversions(&self) -> TokenStream2 {
+        let (true, Some(LeafMessage::Field(member))) = (self.config.self_message, &self.config.message) else {
+            return TokenStream2::new();
+        };
+        let struct_name = &self.struct_def.ident;
+        let (impl_generics, ty_generics, where_clause) = self.struct_def.generics.split_for_impl();
+        quote! {
+            impl #impl_generics From<String> for #struct_name #ty_generics #where_clause {
+                fn from(message: String) -> Self {
+                    Self { #member: message }
+                }
+            }
+
+            impl #impl_generics From<&str> for #struct_name #ty_generics #where_clause {
+                fn from(message: &str) -> Self {
+                    Self { #member: message.to_string() }
+                }
+            }
+
+            impl #impl_generics std::str::FromStr for #struct_name #ty_generics #where_clause {
+                type Err = std::convert::Infallible;
+
+                fn from_str(s: &str) -> ::core::result::Result<Self, Self::Err> {
+                    Ok(Self::from(s))
+                }
+            }
+        }
+    }
+
+    /// Takes the message from a `thiserror`-like `#[error("...")]` attribute on the struct, if the
+    /// message is not given with the `message` keyword.
+    #[cfg(feature = "thiserror_compat")]
+    fn take_error_attribute(mut self) -> syn::Result<Self> {
+        let Some(position) = self.struct_def.attrs.iter().position(|it| it.path().is_ident("error")) else {
+            return Ok(self);
+        };
+        let attribute = self.struct_def.attrs.remove(position);
+        if let Some(message) = &self.config.message {
+            return Err(SynError::new_spanned(
+                attribute,
+                format!("the message cannot be given with both #[error(...)] and keyword {}", message.keyword()),
+            ));
+        }
+        if matches!(self.struct_def.fields, Fields::Unnamed(_)) {
+            return Err(SynError::new_spanned(attribute, "#[error(...)] requires a struct with named fields"));
+        }
+        self.config.message = Some(LeafMessage::Interpolated {
+            args: attribute.parse_args()?,
+            fields: vec![],
+        });
+        Ok(self)
+    }
+
+    /// Lists the named fields of the struct in an interpolated message, checking that a message
+    /// given as a string literal alone refers to fields of the struct only.
+    fn take_interpolated_fields(mut self) -> syn::Result<Self> {
+        let Some(LeafMessage::Interpolated { args, fields }) = &mut self.config.message else {
+            return Ok(self);
+        };
+        *fields = self.struct_def.fields.iter().filter_map(|it| it.ident.clone()).collect();
+        if let Ok(message) = syn::parse2::<LitStr>(args.clone()) {
+            let value = message.value();
+            let mut rest = value.as_str();
+            while let Some(start) = rest.find('{') {
+                if rest[start..].starts_with("{{") {
+                    rest = &rest[start + 2..];
+                    continue;
+                }
+                let placeholder = &rest[start + 1..];
+                let end = placeholder.find('}').unwrap_or(placeholder.len());
+                let name = placeholder[..end].split(':').next().unwrap_or_default().trim();
+                let is_positional = name.is_empty() || name.chars().all(|it| it.is_ascii_digit());
+                if !is_positional && !fields.iter().any(|it| it == name) {
+                    return Err(SynError::new_spanned(
+                        &message,
+                        format!("the message refers to '{}', which is not a named field of {}", name, self.struct_def.ident),
+                    ));
+                }
+                rest = &placeholder[end..];
+            }
+        }
+        Ok(self)
+    }
+
+    pub fn to_token_stream(&self) -> TokenStream {
+        if self.config.message.is_none() {
+            let missing_message = MissingRequiredConfigurationError {
+                keyword: ErrorLeafConfigKeyword::Message.to_string(),
+            };
+            return SynError::new(Span::call_site(), missing_message).to_compile_error().into();
+        }
+        let struct_def = match self.struct_with_hidden_fields() {
+            Ok(struct_def) => struct_def,
+            Err(e) => return e.to_compile_error().into(),
+        };
+        let struct_name = &self.struct_def.ident;
+        let (impl_generics, ty_generics, where_clause) = &self.struct_def.generics.split_for_impl();
+
+        let display_impl = {
+            let write_message = if self.config.precompute {
+                let message_field = precomputed_message_field();
+                quote! { f.write_str(&self.#message_field) }
+            } else {
+                self.config.message.as_ref().expect("message existence is already checked").write_tokens(struct_name)
+            };
+            quote! {
+                impl #impl_generics std::fmt::Display for #struct_name #ty_generics #where_clause {
+                    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        #write_message
+                    }
+                }
+            }
+        };
+        let source_method = source_method(self.config.source_fn.as_ref());
+        let provide_method = provide_method(self.config.source_fn.as_ref());
+        let error_impl = quote! {
+            impl #impl_generics std::error::Error for #struct_name #ty_generics #where_clause {
+                #source_method
+                #provide_method
+            }
+        };
+        let derive_debug = if self.config.derive_debug {
+            quote! {
+                #[derive(Debug)]
+            }
+        } else {
+            TokenStream2::new()
+        };
+        let derive_hash = if self.config.hashable {
+            quote! {
+                #[derive(PartialEq, Eq, Hash)]
+            }
+        } else {
+            TokenStream2::new()
+        };
+        let debug_assertion = if self.config.no_debug && self.struct_def.generics.params.is_empty() {
+            manual_debug_assertion(struct_name, "no_debug")
+        } else {
+            TokenStream2::new()
+        };
+
+        let builder = if self.config.typed_builder {
+            match self.builder() {
+                Ok(builder) => builder,
+                Err(e) => return e.to_compile_error().into(),
+            }
+        } else {
+            TokenStream2::new()
+        };
+
+        let self_message_conversions = self.self_message_conversions();
+        let into_io_impl = into_io_impl(struct_name, &self.struct_def.generics, self.config.into_io.as_ref());
+        let retryable_impl = retryable_impl(struct_name, &self.struct_def.generics, self.config.retryable);
+        let coded_impl = coded_impl(struct_name, &self.struct_def.generics, self.config.code.as_ref());
+        let has_severity_impl = has_severity_impl(struct_name, &self.struct_def.generics);
+        let clone_impl = if self.config.clone_via_string {
+            Self::clone_via_string_impl(&struct_def)
+        } else if self.config.clone_message_only {
+            self.clone_message_only_impl()
+        } else {
+            TokenStream2::new()
+        };
+
+        let inherent_impl = match self.inherent_impl() {
+            Ok(inherent_impl) => inherent_impl,
+            Err(e) => return e.to_compile_error().into(),
+        };
+
+        let result_stream = quote! {
+            #derive_debug
+            #derive_hash
+            #struct_def
+            #display_impl
+            #error_impl
+            #clone_impl
+            #self_message_conversions
+            #builder
+            #inherent_impl
+            #into_io_impl
+            #retryable_impl
+            #coded_impl
+            #has_severity_impl
+            #debug_assertion
+        };
+
+        result_stream.into()
+    }
+
+    /// Generates the `Clone` implementation of `clone_via_string` leaves, which replaces each boxed
+    /// error with a [`MessageError`](hierrorchy::MessageError) carrying its message, and clones the
+    /// other fields.
+    fn clone_via_string_impl(struct_def: &ItemStruct) -> TokenStream2 {
+        let struct_name = &struct_def.ident;
+        let (impl_generics, ty_generics, where_clause) = struct_def.generics.split_for_impl();
+        let field_clones = struct_def.fields.iter().enumerate().map(|(index, field)| {
+            let member = match &field.ident {
+                Some(name) => Member::Named(name.clone()),
+                None => Member::Unnamed(Index::from(index)),
+            };
+            if Self::is_boxed_error(&field.ty) {
+                quote! { #member: Box::new(::hierrorchy::MessageError::new(self.#member.to_string())), }
+            } else {
+                quote! { #member: self.#member.clone(), }
+            }
+        });
+        quote! {
+            impl #impl_generics Clone for #struct_name #ty_generics #where_clause {
+                fn clone(&self) -> Self {
+                    Self {
+                        #( #field_clones )*
+                    }
+                }
+            }
+        }
+    }
+
+    /// Generates the `Clone` implementation of `clone_message_only` leaves, which clones the hidden
+    /// fields, including the precomputed message, and sets the other fields to their default value.
+    fn clone_message_only_impl(&self) -> TokenStream2 {
+        let struct_name = &self.struct_def.ident;
+        let (impl_generics, ty_generics, where_clause) = self.struct_def.generics.split_for_impl();
+        let field_defaults = self.struct_def.fields.iter().filter_map(|it| it.ident.as_ref()).map(|name| {
+            quote! { #name: Default::default(), }
+        });
+        let hidden_clones = self.hidden_fields().into_iter().map(|(name, _, _)| {
+            quote! { #name: Clone::clone(&self.#name), }
+        });
+        quote! {
+            impl #impl_generics Clone for #struct_name #ty_generics #where_clause {
+                fn clone(&self) -> Self {
+                    Self {
+                        #( #field_defaults )*
+                        #( #hidden_clones )*
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns whether the type is a `Box<dyn Error>`, possibly with other bounds (e.g. `Send`).
+    fn is_boxed_error(ty: &Type) -> bool {
+        let Type::Path(type_path) = ty else {
+            return false;
+        };
+        let Some(last_segment) = type_path.path.segments.last() else {
+            return false;
+        };
+        if last_segment.ident != "Box" {
+            return false;
+        }
+        let PathArguments::AngleBracketed(arguments) = &last_segment.arguments else {
+            return false;
+        };
+        matches!(
+            arguments.args.first(),
+            Some(GenericArgument::Type(Type::TraitObject(trait_object)))
+                if trait_object.bounds.iter().any(|bound| matches!(
+                    bound,
+                    TypeParamBound::Trait(trait_bound)
+                        if trait_bound.path.segments.last().is_some_and(|it| it.ident == "Error")
+                ))
+        )
+    }
+
+    /// Returns the fields added to the struct by the macro, with their type and their initial value.
+    fn hidden_fields(&self) -> Vec<(Ident, Type, TokenStream2)> {
+        let mut hidden_fields = vec![];
+        if self.config.precompute {
+            hidden_fields.push((
+                precomputed_message_field(),
+                parse_quote! { String },
+                quote! { String::new() },
+            ));
+        }
+        if self.config.timestamp {
+            hidden_fields.push((
+                timestamp_field(),
+                parse_quote! { std::time::SystemTime },
+                quote! { std::time::SystemTime::now() },
+            ));
+        }
+        hidden_fields
+    }
+
+    /// Returns the struct definition, with the hidden fields added.
+    fn struct_with_hidden_fields(&self) -> syn::Result<ItemStruct> {
+        let mut struct_def = self.struct_def.clone();
+        let hidden_fields = self.hidden_fields();
+        if hidden_fields.is_empty() {
+            return Ok(struct_def);
+        }
+        let Fields::Named(fields) = &mut struct_def.fields else {
+            return Err(SynError::new(
+                self.struct_def.ident.span(),
+                "the configuration of this error leaf requires a struct with named fields",
+            ));
+        };
+        for (name, ty, _) in hidden_fields {
+            fields.named.push(Field::parse_named.parse2(quote! { #name: #ty })?);
+        }
+        Ok(struct_def)
+    }
+
+    /// Generates the `new` constructor, when the struct has hidden fields or is `#[non_exhaustive]`
+    /// (and thus cannot be built with a struct expression outside of its crate).
+    fn constructor(&self) -> TokenStream2 {
+        let hidden_fields = self.hidden_fields();
+        let is_non_exhaustive = self.struct_def.attrs.iter().any(|it| it.path().is_ident("non_exhaustive"));
+        if hidden_fields.is_empty() && !is_non_exhaustive {
+            return TokenStream2::new();
+        }
+        let vis = &self.struct_def.vis;
+        let field_members: Vec<Member> = self
+            .struct_def
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(index, it)| match &it.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(Index::from(index)),
+            })
+            .collect();
+        let field_names: Vec<Ident> = field_members
+            .iter()
+            .map(|it| match it {
+                Member::Named(ident) => ident.clone(),
+                Member::Unnamed(index) => format_ident!("field{}", index.index),
+            })
+            .collect();
+        let field_types: Vec<&Type> = self.struct_def.fields.iter().map(|it| &it.ty).collect();
+        let hidden_inits = hidden_fields.iter().map(|(name, _, init)| quote! { #name: #init, });
+        let (precompute_message, compute_message_helper) = if self.config.precompute {
+            let compute_message = self.config.message.as_ref().expect("message existence is already checked").string_tokens(&self.struct_def.ident);
+            let message_field = precomputed_message_field();
+            (
+                quote! { error.#message_field = error.compute_message(); },
+                quote! {
+                    fn compute_message(&self) -> String {
+                        #compute_message
+                    }
+                },
+            )
+        } else {
+            (TokenStream2::new(), TokenStream2::new())
+        };
+        quote! {
+            /// Creates a new error from the value of each of its fields.
+            #[allow(clippy::too_many_arguments)]
+            #vis fn new(#( #field_names: #field_types ),*) -> Self {
+                #[allow(unused_mut)]
+                let mut error = Self {
+                    #( #field_members: #field_names, )*
+                    #( #hidden_inits )*
+                };
+                #precompute_message
+                error
+            }
+
+            #compute_message_helper
+        }
+    }
+
+    fn inherent_impl(&self) -> syn::Result<TokenStream2> {
+        let struct_name = &self.struct_def.ident;
+        let vis = &self.struct_def.vis;
+        let (impl_generics, ty_generics, where_clause) = self.struct_def.generics.split_for_impl();
+        let mut methods = self.constructor();
+
+        if self.config.timestamp {
+            let created_at = timestamp_field();
+            methods.extend(quote! {
+                /// Returns the time at which this error was created.
+                #vis fn created_at(&self) -> std::time::SystemTime {
+                    self.#created_at
+                }
+            });
+        }
+
+        if let Some(kind_field) = &self.config.expose_kind {
+            let kind_type = self
+                .struct_def
+                .fields
+                .iter()
+                .find(|it| it.ident.as_ref() == Some(kind_field))
+                .map(|it| &it.ty)
+                .ok_or_else(|| SynError::new(kind_field.span(), format!("the struct has no field named '{}'", kind_field)))?;
+            methods.extend(quote! {
+                /// Returns the kind of this error.
+                #vis fn kind(&self) -> &#kind_type {
+                    &self.#kind_field
+                }
+            });
+        }
+
+        if let Some(kind_str) = &self.config.kind_str {
+            methods.extend(quote! {
+                /// The machine-readable kind of this error.
+                #vis const KIND: &'static str = #kind_str;
+
+                /// Returns the machine-readable kind of this error.
+                #vis fn kind_str(&self) -> &'static str {
+                    Self::KIND
+                }
+            });
+        }
+
+        if self.config.log_line {
+            methods.extend(log_line_method(vis, self.config.kind_str.is_some(), self.config.code.is_some()));
+        }
+
+        methods.extend(severity_methods(vis, self.config.severity.as_ref())?);
+
+        if self.config.retryable {
+            methods.extend(retryable_method(vis));
+        }
+
+        methods.extend(code_methods(vis, self.config.code.as_ref()));
+
+        Ok(quote! {
+            impl #impl_generics #struct_name #ty_generics #where_clause {
+                #methods
+            }
+        })
+    }
+
+    fn builder(&self) -> syn::Result<TokenStream2> {
+        let struct_name = &self.struct_def.ident;
+        let vis = &self.struct_def.vis;
+        let generics = &self.struct_def.generics;
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        let builder_name = format_ident!("{}Builder", struct_name);
+        let builder_error_name = format_ident!("{}BuilderError", struct_name);
+
+        let fields = match &self.struct_def.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(SynError::new(
+                    self.struct_def.ident.span(),
+                    format!("keyword {} requires a struct with named fields", ErrorLeafConfigKeyword::TypedBuilder),
+                ));
+            }
+        };
+        let field_names: Vec<&Ident> = fields.iter().map(|it| it.ident.as_ref().expect("named fields always have an ident")).collect();
+        let field_types: Vec<&Type> = fields.iter().map(|it| &it.ty).collect();
+        let field_values = field_names.iter().zip(field_types.iter()).map(|(name, ty)| {
+            if Self::is_option(ty) {
+                quote! { self.#name.unwrap_or(None) }
+            } else {
+                let missing = name.to_string();
+                quote! { self.#name.ok_or(#builder_error_name { field: #missing })? }
+            }
+        });
+        let build_error = if self.hidden_fields().is_empty() {
+            quote! {
+                #struct_name {
+                    #( #field_names: #field_values, )*
+                }
+            }
+        } else {
+            quote! { #struct_name::new(#( #field_values ),*) }
+        };
+        let builder_doc = format!("Builder for [`{}`].", struct_name);
+        let builder_error_doc = format!("Error returned when building a [`{}`] with a required field not set.", struct_name);
+
+        Ok(quote! {
+            #[doc = #builder_doc]
+            #vis struct #builder_name #generics #where_clause {
+                #( #field_names: Option<#field_types>, )*
+            }
+
+            impl #impl_generics Default for #builder_name #ty_generics #where_clause {
+                fn default() -> Self {
+                    Self {
+                        #( #field_names: None, )*
+                    }
+                }
+            }
+
+            impl #impl_generics #builder_name #ty_generics #where_clause {
+                #(
+                    #vis fn #field_names(mut self, value: #field_types) -> Self {
+                        self.#field_names = Some(value);
+                        self
+                    }
+                )*
+
+                #vis fn build(self) -> ::core::result::Result<#struct_name #ty_generics, #builder_error_name> {
+                    Ok(#build_error)
+                }
+            }
+
+            impl #impl_generics #struct_name #ty_generics #where_clause {
+                #vis fn builder() -> #builder_name #ty_generics {
+                    #builder_name::default()
+                }
+            }
+
+            #[doc = #builder_error_doc]
+            #[derive(Debug, Clone, PartialEq, Eq)]
+            #vis struct #builder_error_name {
+                field: &'static str,
+            }
+
+            impl std::fmt::Display for #builder_error_name {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    write!(f, "the required field '{}' is not set", self.field)
+                }
+            }
+
+            impl std::error::Error for #builder_error_name {}
+        })
+    }
+
+    fn is_option(ty: &Type) -> bool {
+        match ty {
+            Type::Path(type_path) => type_path
+                .path
+                .segments
+                .last()
+                .is_some_and(|it| it.ident == "Option"),
+            _ => false,
+        }
+    }
+}
+
+/// An error leaf declared on an enum, with a message for each variant given by a `#[msg(...)]`
+/// attribute.
+pub struct ErrorLeafEnum {
+    config: ErrorLeafConfig,
+    enum_def: ItemEnum,
+}
+
+impl ErrorLeafEnum {
+    pub fn new(config: ErrorLeafConfig, enum_def: ItemEnum) -> ErrorLeafEnum {
+        ErrorLeafEnum { config, enum_def }
+    }
+
+    pub fn to_token_stream(&self) -> TokenStream {
+        match self.expand() {
+            Ok(tokens) => tokens.into(),
+            Err(e) => e.to_compile_error().into(),
+        }
+    }
+
+    fn expand(&self) -> syn::Result<TokenStream2> {
+        self.check_config()?;
+        let enum_name = &self.enum_def.ident;
+        let vis = &self.enum_def.vis;
+        let (impl_generics, ty_generics, where_clause) = self.enum_def.generics.split_for_impl();
+
+        let mut enum_def = self.enum_def.clone();
+        let mut variant_matches = vec![];
+        for variant in enum_def.variants.iter_mut() {
+            let msg_position = variant
+                .attrs
+                .iter()
+                .position(|it| it.path().is_ident("msg"))
+                .ok_or_else(|| SynError::new(variant.ident.span(), "every variant of an error leaf enum requires a #[msg(...)] attribute"))?;
+            let msg = variant.attrs.remove(msg_position);
+            let format_args: TokenStream2 = msg.parse_args()?;
+            let variant_name = &variant.ident;
+            let pattern = match &variant.fields {
+                Fields::Named(fields) => {
+                    let names = fields.named.iter().map(|it| &it.ident);
+                    quote! { Self::#variant_name { #( #names ),* } }
+                }
+                Fields::Unnamed(fields) => {
+                    let names = (0..fields.unnamed.len()).map(|index| format_ident!("_{}", index));
+                    quote! { Self::#variant_name( #( #names ),* ) }
+                }
+                Fields::Unit => quote! { Self::#variant_name },
+            };
+            variant_matches.push(quote! {
+                #[allow(unused_variables)]
+                #pattern => write!(f, #format_args),
+            });
+        }
+
+        let derive_debug = if self.config.derive_debug {
+            quote! { #[derive(Debug)] }
+        } else {
+            TokenStream2::new()
+        };
+        let derive_hash = if self.config.hashable {
+            quote! { #[derive(PartialEq, Eq, Hash)] }
+        } else {
+            TokenStream2::new()
+        };
+        let debug_assertion = if self.config.no_debug && self.enum_def.generics.params.is_empty() {
+            manual_debug_assertion(enum_name, "no_debug")
+        } else {
+            TokenStream2::new()
+        };
+        let mut methods = match &self.config.kind_str {
+            Some(kind_str) => quote! {
+                /// The machine-readable kind of this error.
+                #vis const KIND: &'static str = #kind_str;
+
+                /// Returns the machine-readable kind of this error.
+                #vis fn kind_str(&self) -> &'static str {
+                    Self::KIND
+                }
+            },
+            None => TokenStream2::new(),
+        };
+        if self.config.log_line {
+            methods.extend(log_line_method(vis, self.config.kind_str.is_some(), self.config.code.is_some()));
+        }
+        methods.extend(severity_methods(vis, self.config.severity.as_ref())?);
+        if self.config.retryable {
+            methods.extend(retryable_method(vis));
+        }
+        methods.extend(code_methods(vis, self.config.code.as_ref()));
+        let inherent_impl = quote! {
+            impl #impl_generics #enum_name #ty_generics #where_clause {
+                #methods
+            }
+        };
+        let source_method = source_method(self.config.source_fn.as_ref());
+        let provide_method = provide_method(self.config.source_fn.as_ref());
+        let into_io_impl = into_io_impl(enum_name, &self.enum_def.generics, self.config.into_io.as_ref());
+        let retryable_impl = retryable_impl(enum_name, &self.enum_def.generics, self.config.retryable);
+        let coded_impl = coded_impl(enum_name, &self.enum_def.generics, self.config.code.as_ref());
+        let has_severity_impl = has_severity_impl(enum_name, &self.enum_def.generics);
+
+        Ok(quote! {
+            #derive_debug
+            #derive_hash
+            #enum_def
+
+            impl #impl_generics std::fmt::Display for #enum_name #ty_generics #where_clause {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    match self {
+                        #( #variant_matches )*
+                    }
+                }
+            }
+
+            impl #impl_generics std::error::Error for #enum_name #ty_generics #where_clause {
+                #source_method
+                #provide_method
+            }
+
+            #inherent_impl
+            #into_io_impl
+            #retryable_impl
+            #coded_impl
+            #has_severity_impl
+            #debug_assertion
+        })
+    }
+
+    /// Checks that the configuration does not contain keywords which are only supported on structs.
+    fn check_config(&self) -> syn::Result<()> {
+        let unsupported_keyword = if let Some(message) = &self.config.message {
+            Some(message.keyword())
+        } else if self.config.typed_builder {
+            Some(ErrorLeafConfigKeyword::TypedBuilder)
+        } else if self.config.expose_kind.is_some() {
+            Some(ErrorLeafConfigKeyword::ExposeKind)
+        } else if self.config.precompute {
+            Some(ErrorLeafConfigKeyword::Precompute)
+        } else if self.config.clone_via_string {
+            Some(ErrorLeafConfigKeyword::CloneViaString)
+        } else if self.config.self_message {
+            Some(ErrorLeafConfigKeyword::SelfMessage)
+        } else if self.config.timestamp {
+            Some(ErrorLeafConfigKeyword::Timestamp)
+        } else if self.config.clone_message_only {
+            Some(ErrorLeafConfigKeyword::CloneMessageOnly)
+        } else if self.config.transparent {
+            Some(ErrorLeafConfigKeyword::Transparent)
+        } else if self.config.source.is_some() {
+            Some(ErrorLeafConfigKeyword::Source)
+        } else {
+            None
+        };
+        match unsupported_keyword {
+            Some(keyword) => Err(SynError::new(
+                self.enum_def.ident.span(),
+                format!("keyword {} is not supported on enums", keyword),
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+pub struct ErrorLeafConfig {
+    /// The message of the leaf, required for structs; enums have a message for each variant.
+    message: Option<LeafMessage>,
+    derive_debug: bool,
+    typed_builder: bool,
+    hashable: bool,
+    expose_kind: Option<Ident>,
+    kind_str: Option<LitStr>,
+    precompute: bool,
+    clone_via_string: bool,
+    log_line: bool,
+    self_message: bool,
+    severity: Option<Ident>,
+    source_fn: Option<Expr>,
+    into_io: Option<Ident>,
+    timestamp: bool,
+    clone_message_only: bool,
+    retryable: bool,
+    code: Option<LitInt>,
+    transparent: bool,
+    source: Option<Member>,
+    no_debug: bool,
+}
+
+impl Parse for ErrorLeafConfig {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let mut macro_config_builder = ErrorLeafConfigBuilder::new();
+        // A string literal before the keywords is a message interpolating the fields of the struct.
+        if input.peek(LitStr) {
+            let message: LitStr = input.parse()?;
+            macro_config_builder.set_message(LeafMessage::Interpolated {
+                args: quote! { #message },
+                fields: vec![],
+            });
+            if !input.is_empty() {
+                let _: Token![,] = input.parse()?;
+            }
+        }
+        while !input.is_empty() {
+            let keyword: Ident = input.parse()?;
+            match keyword
+                .to_string()
+                .parse::<ErrorLeafConfigKeyword>()
+                .map_err(|it| SynError::new(keyword.span(), it))?
+            {
+                ErrorLeafConfigKeyword::DeriveDebug => {
+                    let _: Token![=] = input.parse()?;
+                    let value: LitBool = input.parse()?;
+                    macro_config_builder.set_derive_debug(value.value());
+                }
+                ErrorLeafConfigKeyword::TypedBuilder => {
+                    macro_config_builder.set_typed_builder(Self::parse_flag(input)?);
+                }
+                ErrorLeafConfigKeyword::Hashable => {
+                    macro_config_builder.set_hashable(Self::parse_flag(input)?);
+                }
+                ErrorLeafConfigKeyword::ExposeKind => {
+                    let _: Token![=] = input.parse()?;
+                    macro_config_builder.set_expose_kind(input.parse()?);
+                }
+                ErrorLeafConfigKeyword::Precompute => {
+                    macro_config_builder.set_precompute(Self::parse_flag(input)?);
+                }
+                ErrorLeafConfigKeyword::CloneViaString => {
+                    macro_config_builder.set_clone_via_string(Self::parse_flag(input)?);
+                }
+                ErrorLeafConfigKeyword::KindStr => {
+                    let _: Token![=] = input.parse()?;
+                    macro_config_builder.set_kind_str(input.parse()?);
+                }
+                ErrorLeafConfigKeyword::Message => {
+                    let _: Token![=] = input.parse()?;
+                    let value: Expr = input.parse()?;
+                    let message = match value {
+                        Expr::Macro(ExprMacro { mac, .. })
+                            if mac.path.segments.last().expect("A Macro call must have a last path segment").ident == "format" =>
+                        {
+                            LeafMessage::Format(mac)
+                        }
+                        other => LeafMessage::Const(other),
+                    };
+                    Self::check_message_not_set(&macro_config_builder, &keyword)?;
+                    macro_config_builder.set_message(message);
+                }
+                ErrorLeafConfigKeyword::LogLine => {
+                    macro_config_builder.set_log_line(Self::parse_flag(input)?);
+                }
+                ErrorLeafConfigKeyword::SelfMessage => {
+                    macro_config_builder.set_self_message(Self::parse_flag(input)?);
+                }
+                ErrorLeafConfigKeyword::Severity => {
+                    let _: Token![=] = input.parse()?;
+                    macro_config_builder.set_severity(input.parse()?);
+                }
+                ErrorLeafConfigKeyword::SourceFn => {
+                    let _: Token![=] = input.parse()?;
+                    macro_config_builder.set_source_fn(input.parse()?);
+                }
+                ErrorLeafConfigKeyword::IntoIo => {
+                    let _: Token![=] = input.parse()?;
+                    macro_config_builder.set_into_io(input.parse()?);
+                }
+                ErrorLeafConfigKeyword::Timestamp => {
+                    macro_config_builder.set_timestamp(Self::parse_flag(input)?);
+                }
+                ErrorLeafConfigKeyword::CloneMessageOnly => {
+                    macro_config_builder.set_clone_message_only(Self::parse_flag(input)?);
+                }
+                ErrorLeafConfigKeyword::Fmt => {
+                    let _: Token![=] = input.parse()?;
+                    let closure: ExprClosure = input.parse()?;
+                    Self::check_message_not_set(&macro_config_builder, &keyword)?;
+                    macro_config_builder.set_message(LeafMessage::Fmt(closure));
+                }
+                ErrorLeafConfigKeyword::Plural => {
+                    let content;
+                    parenthesized!(content in input);
+                    let count: Expr = content.parse()?;
+                    let _: Token![,] = content.parse()?;
+                    let singular: LitStr = content.parse()?;
+                    let _: Token![,] = content.parse()?;
+                    let plural: LitStr = content.parse()?;
+                    let _: Option<Token![,]> = content.parse()?;
+                    Self::check_message_not_set(&macro_config_builder, &keyword)?;
+                    macro_config_builder.set_message(LeafMessage::Plural { count, singular, plural });
+                }
+                ErrorLeafConfigKeyword::Retryable => {
+                    macro_config_builder.set_retryable(Self::parse_flag(input)?);
+                }
+                ErrorLeafConfigKeyword::Code => {
+                    let _: Token![=] = input.parse()?;
+                    macro_config_builder.set_code(input.parse()?);
+                }
+                ErrorLeafConfigKeyword::Transparent => {
+                    macro_config_builder.set_transparent(Self::parse_flag(input)?);
+                }
+                ErrorLeafConfigKeyword::Source => {
+                    let _: Token![=] = input.parse()?;
+                    macro_config_builder.set_source(input.parse()?);
+                }
+                ErrorLeafConfigKeyword::NoDebug => {
+                    macro_config_builder.set_no_debug(Self::parse_flag(input)?);
+                }
+            }
+            if !input.is_empty() {
+                let _: Token![,] = input.parse()?;
+            }
+        }
+        if macro_config_builder.no_debug == Some(true) && macro_config_builder.derive_debug == Some(true) {
+            return Err(SynError::new(
+                input.span(),
+                format!(
+                    "keyword {} cannot be used with {} = true",
+                    ErrorLeafConfigKeyword::NoDebug,
+                    ErrorLeafConfigKeyword::DeriveDebug
+                ),
+            ));
+        }
+        macro_config_builder
+            .build()
+            .map_err(|it| SynError::new(input.span(), it))
+    }
+}
+
+impl ErrorLeafConfig {
+    /// Parses a flag keyword, which can be written either alone (meaning `true`) or as
+    /// `<keyword> = <bool>`.
+    fn parse_flag(input: syn::parse::ParseStream) -> syn::Result<bool> {
+        if input.peek(Token![=]) {
+            let _: Token![=] = input.parse()?;
+            let value: LitBool = input.parse()?;
+            Ok(value.value())
+        } else {
+            Ok(true)
+        }
+    }
+
+    /// Checks that the message is not already given by another one of `message`, `plural` and
+    /// `fmt`, which all set it, nor as a string before the keywords.
+    fn check_message_not_set(builder: &ErrorLeafConfigBuilder, keyword: &Ident) -> syn::Result<()> {
+        match &builder.message {
+            Some(LeafMessage::Interpolated { .. }) => Err(SynError::new(
+                keyword.span(),
+                format!("keyword {} cannot be used with a message given as a string", keyword),
+            )),
+            Some(message) if *keyword != message.keyword().to_string() => Err(SynError::new(
+                keyword.span(),
+                format!("keyword {} cannot be used with keyword {}", keyword, message.keyword()),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+struct ErrorLeafConfigBuilder {
+    message: Option<LeafMessage>,
+    derive_debug: Option<bool>,
+    typed_builder: Option<bool>,
+    hashable: Option<bool>,
+    expose_kind: Option<Ident>,
+    kind_str: Option<LitStr>,
+    precompute: Option<bool>,
+    clone_via_string: Option<bool>,
+    log_line: Option<bool>,
+    self_message: Option<bool>,
+    severity: Option<Ident>,
+    source_fn: Option<Expr>,
+    into_io: Option<Ident>,
+    timestamp: Option<bool>,
+    clone_message_only: Option<bool>,
+    retryable: Option<bool>,
+    code: Option<LitInt>,
+    transparent: Option<bool>,
+    source: Option<Member>,
+    no_debug: Option<bool>,
+}
+
+impl ErrorLeafConfigBuilder {
+    pub fn new() -> Self {
+        ErrorLeafConfigBuilder {
+            message: None,
+            derive_debug: None,
+            typed_builder: None,
+            hashable: None,
+            expose_kind: None,
+            kind_str: None,
+            precompute: None,
+            clone_via_string: None,
+            log_line: None,
+            self_message: None,
+            severity: None,
+            source_fn: None,
+            into_io: None,
+            timestamp: None,
+            clone_message_only: None,
+            retryable: None,
+            code: None,
+            transparent: None,
+            source: None,
+            no_debug: None,
+        }
+    }
+
+    pub fn set_message(&mut self, message: LeafMessage) {
+        self.message = Some(message);
+    }
+
+    pub fn set_derive_debug(&mut self, derive_debug: bool) {
+        self.derive_debug = Some(derive_debug);
+    }
+
+    pub fn set_typed_builder(&mut self, typed_builder: bool) {
+        self.typed_builder = Some(typed_builder);
+    }
+
+    pub fn set_hashable(&mut self, hashable: bool) {
+        self.hashable = Some(hashable);
+    }
+
+    pub fn set_expose_kind(&mut self, expose_kind: Ident) {
+        self.expose_kind = Some(expose_kind);
+    }
+
+    pub fn set_kind_str(&mut self, kind_str: LitStr) {
+        self.kind_str = Some(kind_str);
+    }
+
+    pub fn set_precompute(&mut self, precompute: bool) {
+        self.precompute = Some(precompute);
+    }
+
+    pub fn set_clone_via_string(&mut self, clone_via_string: bool) {
+        self.clone_via_string = Some(clone_via_string);
+    }
+
+    pub fn set_log_line(&mut self, log_line: bool) {
+        self.log_line = Some(log_line);
+    }
+
+    pub fn set_self_message(&mut self, self_message: bool) {
+        self.self_message = Some(self_message);
+    }
+
+    pub fn set_severity(&mut self, severity: Ident) {
+        self.severity = Some(severity);
+    }
+
+    pub fn set_source_fn(&mut self, source_fn: Expr) {
+        self.source_fn = Some(source_fn);
+    }
+
+    pub fn set_into_io(&mut self, into_io: Ident) {
+        self.into_io = Some(into_io);
+    }
+
+    pub fn set_timestamp(&mut self, timestamp: bool) {
+        self.timestamp = Some(timestamp);
+    }
+
+    pub fn set_clone_message_only(&mut self, clone_message_only: bool) {
+        self.clone_message_only = Some(clone_message_only);
+    }
+
+    pub fn set_retryable(&mut self, retryable: bool) {
+        self.retryable = Some(retryable);
+    }
+
+    pub fn set_code(&mut self, code: LitInt) {
+        self.code = Some(code);
+    }
+
+    pub fn set_transparent(&mut self, transparent: bool) {
+        self.transparent = Some(transparent);
+    }
+
+    pub fn set_source(&mut self, source: Member) {
+        self.source = Some(source);
+    }
+
+    pub fn set_no_debug(&mut self, no_debug: bool) {
+        self.no_debug = Some(no_debug);
+    }
+
+    pub fn build(&self) -> Result<ErrorLeafConfig, MissingRequiredConfigurationError> {
+        Ok(ErrorLeafConfig {
+            message: self.message.clone(),
+            derive_debug: self.derive_debug.unwrap_or(self.no_debug != Some(true)),
+            typed_builder: self.typed_builder.unwrap_or(false),
+            hashable: self.hashable.unwrap_or(false),
+            expose_kind: self.expose_kind.clone(),
+            kind_str: self.kind_str.clone(),
+            precompute: self.precompute.unwrap_or(false),
+            clone_via_string: self.clone_via_string.unwrap_or(false),
+            log_line: self.log_line.unwrap_or(false),
+            self_message: self.self_message.unwrap_or(false),
+            severity: self.severity.clone(),
+            source_fn: self.source_fn.clone(),
+            into_io: self.into_io.clone(),
+            timestamp: self.timestamp.unwrap_or(false),
+            clone_message_only: self.clone_message_only.unwrap_or(false),
+            retryable: self.retryable.unwrap_or(false),
+            code: self.code.clone(),
+            transparent: self.transparent.unwrap_or(false),
+            source: self.source.clone(),
+            no_debug: self.no_debug.unwrap_or(false),
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ErrorLeafConfigKeyword {
+    Message,
+    DeriveDebug,
+    TypedBuilder,
+    Hashable,
+    ExposeKind,
+    KindStr,
+    Precompute,
+    CloneViaString,
+    LogLine,
+    SelfMessage,
+    Severity,
+    SourceFn,
+    IntoIo,
+    Timestamp,
+    CloneMessageOnly,
+    Plural,
+    Fmt,
+    Retryable,
+    Code,
+    Transparent,
+    Source,
+    NoDebug,
+}
+
+impl ErrorLeafConfigKeyword {
+    /// Every keyword, in the order they are listed when an unknown one is found.
+    const ALL: &'static [Self] = &[
+        Self::Message,
+        Self::DeriveDebug,
+        Self::TypedBuilder,
+        Self::Hashable,
+        Self::ExposeKind,
+        Self::KindStr,
+        Self::Precompute,
+        Self::CloneViaString,
+        Self::LogLine,
+        Self::SelfMessage,
+        Self::Severity,
+        Self::SourceFn,
+        Self::IntoIo,
+        Self::Timestamp,
+        Self::CloneMessageOnly,
+        Self::Plural,
+        Self::Fmt,
+        Self::Retryable,
+        Self::Code,
+        Self::Transparent,
+        Self::Source,
+        Self::NoDebug,
+    ];
+}
+
+impl Display for ErrorLeafConfigKeyword {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}",
+            match self {
+                Self::Message => "message",
+                Self::DeriveDebug => "derive_debug",
+                Self::TypedBuilder => "typed_builder",
+                Self::Hashable => "hashable",
+                Self::ExposeKind => "expose_kind",
+                Self::KindStr => "kind_str",
+                Self::Precompute => "precompute",
+                Self::CloneViaString => "clone_via_string",
+                Self::LogLine => "log_line",
+                Self::SelfMessage => "self_message",
+                Self::Severity => "severity",
+                Self::SourceFn => "source_fn",
+                Self::IntoIo => "into_io",
+                Self::Timestamp => "timestamp",
+                Self::CloneMessageOnly => "clone_message_only",
+                Self::Plural => "plural",
+                Self::Fmt => "fmt",
+                Self::Retryable => "retryable",
+                Self::Code => "code",
+                Self::Transparent => "transparent",
+                Self::Source => "source",
+                Self::NoDebug => "no_debug",
+            }
+        )
+    }
+}
+
+impl FromStr for ErrorLeafConfigKeyword {
+    type Err = UnknownConfigKeywordError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "message" => Ok(Self::Message),
+            "derive_debug" => Ok(Self::DeriveDebug),
+            "typed_builder" => Ok(Self::TypedBuilder),
+            "hashable" => Ok(Self::Hashable),
+            "expose_kind" => Ok(Self::ExposeKind),
+            "kind_str" => Ok(Self::KindStr),
+            "precompute" => Ok(Self::Precompute),
+            "clone_via_string" => Ok(Self::CloneViaString),
+            "log_line" => Ok(Self::LogLine),
+            "self_message" => Ok(Self::SelfMessage),
+            "severity" => Ok(Self::Severity),
+            "source_fn" => Ok(Self::SourceFn),
+            "into_io" => Ok(Self::IntoIo),
+            "timestamp" => Ok(Self::Timestamp),
+            "clone_message_only" => Ok(Self::CloneMessageOnly),
+            "plural" => Ok(Self::Plural),
+            "fmt" => Ok(Self::Fmt),
+            "retryable" => Ok(Self::Retryable),
+            "code" => Ok(Self::Code),
+            "transparent" => Ok(Self::Transparent),
+            "source" => Ok(Self::Source),
+            "no_debug" => Ok(Self::NoDebug),
+            _ => Err(UnknownConfigKeywordError {
+                keyword: s.to_string(),
+            }),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct UnknownConfigKeywordError {
+    keyword: String,
+}
+
+impl Display for UnknownConfigKeywordError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "unknown keyword '{}' in macro configuration, expected one of: {}",
+            self.keyword,
+            ErrorLeafConfigKeyword::ALL
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        )
+    }
+}
+
+impl Error for UnknownConfigKeywordError {}
+
+#[derive(Debug, Clone)]
+pub struct MissingRequiredConfigurationError {
+    keyword: String,
+}
+
+impl Display for MissingRequiredConfigurationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "the required keyword '{}' is missing in macro configuration",
+            self.keyword
+        )
+    }
+}
+
+impl Error for MissingRequiredConfigurationError {}
@@ -0,0 +1,2377 @@
+use proc_macro::TokenStream;
+use proc_macro2::{Group, TokenStream as TokenStream2, TokenTree};
+use quote::{ToTokens, format_ident, quote, quote_spanned};
+use syn::{
+    Attribute, Error as SynError, Expr, GenericArgument, GenericParam, Generics, Ident, LitInt, LitStr, Path,
+    PathArguments, Token, Type, TypeTraitObject, Visibility,
+    braced, parenthesized, parse::Parse, parse_quote, parse_quote_spanned, punctuated::Punctuated, spanned::Spanned,
+};
+
+/// Generates the expression of the separator between the prefix of a node and the message of its
+/// source, used unless the node is declared with `display(separator = "...")` or
+/// `display(raw_prefix)`.
+///
+/// It is the `DEFAULT_NODE_SEP` constant at the root of the crate using the macro, if there is one,
+/// and `hierrorchy::DEFAULT_NODE_SEP` otherwise: the glob import of the crate root in the inner
+/// block shadows the one of the default in the outer block, and brings nothing if the crate does
+/// not define the constant.
+pub(crate) fn default_node_separator() -> TokenStream2 {
+    quote! {
+        {
+            #[allow(unused_imports)]
+            use ::hierrorchy::__private::defaults::*;
+            {
+                #[allow(unused_imports)]
+                use crate::*;
+                DEFAULT_NODE_SEP
+            }
+        }
+    }
+}
+
+pub struct ErrorNode {
+    config: ErrorNodeConfig,
+    visibility: Visibility,
+    node_name: Ident,
+    generics: Generics,
+    variants: Vec<ErrorNodeVariant>,
+    message_prefix: Option<MessagePrefix>,
+    /// The common prefix of the nodes declared with [`error_nodes`](crate::error_nodes), which
+    /// precedes the literal prefix (or the name) of the node.
+    common_prefix: Option<LitStr>,
+}
+
+/// Several error nodes declared together, whose prefixes start with a common one:
+/// `prefix "<prefix>"; <node>; <node>; ...`.
+pub struct ErrorNodes {
+    nodes: Vec<ErrorNode>,
+}
+
+impl Parse for ErrorNodes {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let keyword: Ident = input.parse()?;
+        if keyword != "prefix" {
+            return Err(SynError::new(keyword.span(), "expected 'prefix'"));
+        }
+        let common_prefix: LitStr = input.parse()?;
+        ErrorNode::check_literal_prefix(&common_prefix)?;
+        let _: Token![;] = input.parse()?;
+        let mut nodes = vec![];
+        while !input.is_empty() {
+            let mut node: ErrorNode = input.parse()?;
+            node.scope_prefix(&common_prefix);
+            nodes.push(node);
+            if !input.is_empty() {
+                let _: Token![;] = input.parse()?;
+            }
+        }
+        Ok(ErrorNodes { nodes })
+    }
+}
+
+impl ErrorNodes {
+    pub fn to_token_stream(&self) -> TokenStream {
+        TokenStream::from_iter(self.nodes.iter().map(ErrorNode::to_token_stream))
+    }
+}
+
+/// A variant of the node, with the type of the wrapped error.
+struct ErrorNodeVariant {
+    config: ErrorNodeVariantConfig,
+    /// The name of the variant, given with `Name = Error`.
+    ident: Option<Ident>,
+    path: Path,
+    discriminant: Option<LitInt>,
+    /// The errors which are converted into the node through the error of this variant, declared
+    /// with `+Variant(Error1, Error2)`.
+    lifted: Vec<Path>,
+    /// The `..` declaring the catch-all `Other` variant, wrapping any boxed error.
+    catch_all: Option<Token![..]>,
+}
+
+impl Parse for ErrorNodeVariant {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let attributes = input.call(Attribute::parse_outer)?;
+        let mut config = ErrorNodeVariantConfig::from_attributes(&attributes)?;
+        if input.peek(Token![..]) {
+            let catch_all: Token![..] = input.parse()?;
+            return Ok(ErrorNodeVariant {
+                config,
+                ident: Some(Ident::new("Other", catch_all.spans[0])),
+                path: parse_quote_spanned! { catch_all.spans[0] => Box<dyn std::error::Error + Send + Sync> },
+                discriminant: None,
+                lifted: vec![],
+                catch_all: Some(catch_all),
+            });
+        }
+        // A name is followed by `=` and the wrapped error, while a discriminant follows the error.
+        let ident = if input.peek(Ident) && input.peek2(Token![=]) && !input.peek3(LitInt) {
+            let ident: Ident = input.parse()?;
+            let _: Token![=] = input.parse()?;
+            Some(ident)
+        } else {
+            None
+        };
+        let is_lifting = input.peek(Token![+]);
+        if is_lifting {
+            let _: Token![+] = input.parse()?;
+        }
+        let path: Path = input.parse()?;
+        if let Some(accepted) = config
+            .accepts
+            .iter()
+            .find(|it| it.to_token_stream().to_string() == path.to_token_stream().to_string())
+        {
+            return Err(SynError::new_spanned(
+                accepted,
+                "the wrapped error is already converted into the node, it must not be listed in accepts",
+            ));
+        }
+        let mut lifted: Vec<Path> = if is_lifting {
+            let content;
+            parenthesized!(content in input);
+            content
+                .parse_terminated(Path::parse, Token![,])?
+                .into_iter()
+                .collect()
+        } else {
+            vec![]
+        };
+        lifted.extend(config.lifted.iter().cloned());
+        // A message replacing the prefix for this variant follows the error: `Error: "message: {}"`.
+        if input.peek(Token![:]) {
+            let _: Token![:] = input.parse()?;
+            let message: LitStr = input.parse()?;
+            if config.display.is_some() {
+                return Err(SynError::new_spanned(
+                    message,
+                    "a variant cannot have both a message and a #[display] attribute",
+                ));
+            }
+            config.display = Some(Self::message_display(&message, config.source_accessor.as_ref()));
+        }
+        let discriminant = if input.peek(Token![=]) {
+            let _: Token![=] = input.parse()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        Ok(ErrorNodeVariant {
+            config,
+            ident,
+            path,
+            discriminant,
+            lifted,
+            catch_all: None,
+        })
+    }
+}
+
+impl ErrorNodeVariant {
+    /// Returns the display function of a variant printed with the given message, where `{}` is
+    /// replaced with the error of the variant.
+    fn message_display(message: &LitStr, source_accessor: Option<&TokenStream2>) -> Expr {
+        let unescaped = message.value().replace("{{", "").replace("}}", "");
+        if unescaped.contains("{}") || unescaped.contains("{:") {
+            parse_quote! { |err, f| write!(f, #message, err #source_accessor) }
+        } else {
+            parse_quote! { |_, f| write!(f, #message) }
+        }
+    }
+
+    /// Returns the name of the variant at the given index in the node: the one given in the
+    /// declaration, or `Variant<index>`.
+    fn name(&self, index: usize) -> Ident {
+        match &self.ident {
+            Some(ident) => ident.clone(),
+            None => format_ident!("Variant{}", index),
+        }
+    }
+
+    /// Returns the expression of the error of the variant, bound to `err`: the wrapped value
+    /// itself, or the result of its source accessor.
+    fn error_tokens(&self) -> TokenStream2 {
+        match &self.config.source_accessor {
+            Some(accessor) => quote! { err #accessor },
+            None => self.value_tokens(),
+        }
+    }
+
+    /// Returns the expression of the wrapped value of the variant, bound to `err`. A boxed trait
+    /// object is unboxed, as `Box<dyn Trait>` does not implement `Error` itself.
+    fn value_tokens(&self) -> TokenStream2 {
+        match (self.config.arc, self.boxed_trait_object().is_some()) {
+            (true, true) => quote! { &***err },
+            (true, false) | (false, true) => quote! { &**err },
+            (false, false) => quote! { err },
+        }
+    }
+
+    /// Returns the trait object of a variant wrapping a `Box<dyn Trait>`.
+    fn boxed_trait_object(&self) -> Option<&TypeTraitObject> {
+        let last_segment = self.path.segments.last()?;
+        if last_segment.ident != "Box" {
+            return None;
+        }
+        let PathArguments::AngleBracketed(arguments) = &last_segment.arguments else {
+            return None;
+        };
+        match arguments.args.first()? {
+            GenericArgument::Type(Type::TraitObject(trait_object)) => Some(trait_object),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the wrapped error type, as written in the declaration of the variant.
+    fn type_name(&self) -> String {
+        let mut name = String::new();
+        write_type_name(&mut name, self.path.to_token_stream());
+        name
+    }
+
+    /// Returns the expression of the wrapped error of the variant, as declared, bound to `err`: the
+    /// field itself, or the content of its `Arc`.
+    fn declared_value_tokens(&self) -> TokenStream2 {
+        if self.config.arc {
+            quote! { &**err }
+        } else {
+            quote! { err }
+        }
+    }
+
+    /// Returns the type of the field of the variant: the wrapped error, or an `Arc` of it.
+    fn field_type(&self) -> TokenStream2 {
+        let path = &self.path;
+        if self.config.arc {
+            quote! { std::sync::Arc<#path> }
+        } else {
+            path.to_token_stream()
+        }
+    }
+
+    /// Returns the expression of the field of the variant from the wrapped error `value`.
+    fn wrap_tokens(&self, value: TokenStream2) -> TokenStream2 {
+        if self.config.arc {
+            quote! { std::sync::Arc::new(#value) }
+        } else {
+            value
+        }
+    }
+}
+
+/// Converts the given `UpperCamelCase` name to `snake_case`, keeping acronyms together, e.g.
+/// `HTTPTimeout` to `http_timeout`.
+fn snake_case(name: &str) -> String {
+    let chars: Vec<char> = name.chars().collect();
+    let mut snake_case = String::new();
+    for (index, c) in chars.iter().enumerate() {
+        if c.is_uppercase() && index > 0 {
+            let previous = chars[index - 1];
+            let next_is_lowercase = chars.get(index + 1).is_some_and(|it| it.is_lowercase());
+            if previous.is_lowercase() || previous.is_ascii_digit() || (previous.is_uppercase() && next_is_lowercase) {
+                snake_case.push('_');
+            }
+        }
+        snake_case.extend(c.to_lowercase());
+    }
+    snake_case
+}
+
+/// Writes the given type tokens as they would be written in the source, like `stringify!`, which
+/// cannot be used as it puts spaces around the punctuation of tokens coming from a procedural macro
+/// (e.g. `std :: io :: Error`). Words are separated by a space, as are `+`, `=` and the tokens after
+/// a `,`; the other punctuation is not.
+fn write_type_name(name: &mut String, tokens: TokenStream2) {
+    let mut previous: Option<TokenTree> = None;
+    let is_word = |token: &TokenTree| matches!(token, TokenTree::Ident(_) | TokenTree::Literal(_));
+    let is_spaced = |token: &TokenTree| matches!(token, TokenTree::Punct(punct) if matches!(punct.as_char(), '+' | '='));
+    for token in tokens {
+        let needs_space = previous.as_ref().is_some_and(|previous| {
+            (is_word(previous) && is_word(&token))
+                || is_spaced(previous)
+                || is_spaced(&token)
+                || matches!(previous, TokenTree::Punct(punct) if punct.as_char() == ',')
+        });
+        if needs_space {
+            name.push(' ');
+        }
+        match &token {
+            TokenTree::Group(group) => {
+                let (open, close) = match group.delimiter() {
+                    proc_macro2::Delimiter::Parenthesis => ("(", ")"),
+                    proc_macro2::Delimiter::Bracket => ("[", "]"),
+                    proc_macro2::Delimiter::Brace => ("{", "}"),
+                    proc_macro2::Delimiter::None => ("", ""),
+                };
+                name.push_str(open);
+                write_type_name(name, group.stream());
+                name.push_str(close);
+            }
+            _ => name.push_str(&token.to_string()),
+        }
+        previous = Some(token);
+    }
+}
+
+/// The configuration of a variant of an error node, given with attributes before the variant.
+#[derive(Default)]
+struct ErrorNodeVariantConfig {
+    /// A predicate on the wrapped error, used to generate a fallible constructor for the variant.
+    guard: Option<Expr>,
+    /// A text appended after the source when printing the variant.
+    suffix: Option<LitStr>,
+    /// Additional types converted into the wrapped error with `Into`.
+    accepts: Vec<Type>,
+    /// Whether to skip the `From` implementation for the wrapped error.
+    no_from: bool,
+    /// The method calls returning the error of a wrapped value which is not an error itself,
+    /// like `.inner()`.
+    source_accessor: Option<TokenStream2>,
+    /// Whether to also convert borrowed errors, by cloning them.
+    owned: bool,
+    /// Whether to store the wrapped error in an `Arc`, so that cloning the node is cheap.
+    arc: bool,
+    /// A function writing the message of the node for this variant, instead of the prefix and the
+    /// source.
+    display: Option<Expr>,
+    /// The errors converted into the node through the error of this variant, declared with
+    /// `#[lift(Error1, Error2)]`.
+    lifted: Vec<Path>,
+    /// The prefix printed before the source for this variant, instead of the one of the node.
+    prefix: Option<Expr>,
+    /// Whether the node has no source for this variant, ending the source chain at the node.
+    terminal: bool,
+}
+
+/// The attributes accepted on the variants of an error node.
+const VARIANT_ATTRIBUTES: &[&str] =
+    &["guard", "prefix", "suffix", "display", "no_from", "owned", "arc", "accepts", "lift", "source", "terminal"];
+
+/// The attributes accepted on an error node.
+const NODE_ATTRIBUTES: &[&str] = &[
+    "display", "source", "debug", "no_debug", "derive", "doc", "kind_str", "severity", "log_line", "repr",
+    "default", "from_infallible", "erased", "cache_root_cause", "unique_prefix", "backtrace",
+    "with_context", "result",
+];
+
+/// Generates a static assertion that an error declared with the given option, which skips the
+/// `Debug` derive, has a manual `Debug` implementation, explaining why it is required when it is
+/// missing.
+pub(crate) fn manual_debug_assertion(type_name: &Ident, option: &str) -> TokenStream2 {
+    let message = format!(
+        "`{}` is declared with {}, but it does not implement `Debug`",
+        type_name, option
+    );
+    let note = format!(
+        "`std::error::Error` requires `Debug`, so errors declared with {} must implement it manually",
+        option
+    );
+    quote! {
+        const _: () = {
+            #[diagnostic::on_unimplemented(
+                message = #message,
+                label = "missing manual `Debug` implementation",
+                note = #note
+            )]
+            trait ManualDebug {}
+
+            impl<T: std::fmt::Debug> ManualDebug for T {}
+
+            fn assert_manual_debug<T: ManualDebug>() {}
+
+            #[allow(dead_code)]
+            fn check() {
+                assert_manual_debug::<#type_name>();
+            }
+        };
+    }
+}
+
+/// Returns the given generics with an additional `'static` bound on each type parameter.
+pub(crate) fn static_generics(generics: &Generics) -> Generics {
+    let mut generics = generics.clone();
+    let type_params: Vec<Ident> = generics
+        .params
+        .iter()
+        .filter_map(|it| match it {
+            GenericParam::Type(type_param) => Some(type_param.ident.clone()),
+            _ => None,
+        })
+        .collect();
+    if !type_params.is_empty() {
+        let where_clause = generics.make_where_clause();
+        for type_param in type_params {
+            where_clause.predicates.push(parse_quote! { #type_param: 'static });
+        }
+    }
+    generics
+}
+
+/// Builds the message of an unknown attribute or option, listing the valid ones.
+fn expected_one_of(message: &str, valid: &[&str]) -> String {
+    format!("{}, expected one of: {}", message, valid.join(", "))
+}
+
+impl ErrorNodeVariantConfig {
+    /// Returns whether the variant has no attribute.
+    fn is_empty(&self) -> bool {
+        self.guard.is_none()
+            && self.suffix.is_none()
+            && self.accepts.is_empty()
+            && !self.no_from
+            && self.source_accessor.is_none()
+            && !self.owned
+            && !self.arc
+            && self.display.is_none()
+            && self.lifted.is_empty()
+            && self.prefix.is_none()
+            && !self.terminal
+    }
+
+    fn from_attributes(attributes: &[Attribute]) -> syn::Result<Self> {
+        let mut config = ErrorNodeVariantConfig::default();
+        for attribute in attributes {
+            if attribute.path().is_ident("guard") {
+                config.guard = Some(attribute.parse_args()?);
+            } else if attribute.path().is_ident("suffix") {
+                config.suffix = Some(attribute.parse_args()?);
+            } else if attribute.path().is_ident("no_from") {
+                attribute.meta.require_path_only()?;
+                config.no_from = true;
+            } else if attribute.path().is_ident("owned") {
+                attribute.meta.require_path_only()?;
+                config.owned = true;
+            } else if attribute.path().is_ident("lift") {
+                config
+                    .lifted
+                    .extend(attribute.parse_args_with(Punctuated::<Path, Token![,]>::parse_terminated)?);
+            } else if attribute.path().is_ident("display") {
+                config.display = Some(attribute.parse_args()?);
+            } else if attribute.path().is_ident("prefix") {
+                config.prefix = Some(attribute.parse_args()?);
+            } else if attribute.path().is_ident("arc") {
+                attribute.meta.require_path_only()?;
+                config.arc = true;
+            } else if attribute.path().is_ident("terminal") {
+                attribute.meta.require_path_only()?;
+                config.terminal = true;
+            } else if attribute.path().is_ident("accepts") {
+                config
+                    .accepts
+                    .extend(attribute.parse_args_with(Punctuated::<Type, Token![,]>::parse_terminated)?);
+            } else if attribute.path().is_ident("source") {
+                let accessor: TokenStream2 = attribute.parse_args()?;
+                if !matches!(accessor.clone().into_iter().next(), Some(TokenTree::Punct(punct)) if punct.as_char() == '.') {
+                    return Err(SynError::new_spanned(
+                        attribute,
+                        "the source accessor must start with a method call or a field access, like .inner()",
+                    ));
+                }
+                config.source_accessor = Some(accessor);
+            } else {
+                return Err(SynError::new_spanned(
+                    attribute,
+                    expected_one_of("unsupported attribute on error node variant", VARIANT_ATTRIBUTES),
+                ));
+            }
+        }
+        Ok(config)
+    }
+}
+
+/// The parts of the node declaration following its attributes and visibility.
+type ErrorNodeBody = (Ident, Generics, Vec<ErrorNodeVariant>, Option<MessagePrefix>);
+
+/// The prefix printed before the source of the node.
+enum MessagePrefix {
+    Literal(LitStr),
+    Expr(Expr),
+}
+
+/// The configuration of an error node, given with attributes before the node declaration.
+#[derive(Default)]
+struct ErrorNodeConfig {
+    cache_prefix: bool,
+    numbered: bool,
+    /// Whether to separate the prefix from the source with a space instead of `: `.
+    raw_prefix: bool,
+    /// The separator between the prefix and the source, replacing the default one.
+    separator: Option<LitStr>,
+    transparent: bool,
+    collapse_source: bool,
+    repr: Option<Ident>,
+    pretty_debug: bool,
+    no_debug: bool,
+    kind_str: bool,
+    severity: bool,
+    log_line: bool,
+    partial_eq: bool,
+    clone: bool,
+    /// The `PartialOrd` path of `#[derive(PartialOrd)]`, kept to report that it requires `Ord`.
+    partial_ord: Option<Path>,
+    /// The `Ord` path of `#[derive(Ord)]`, kept to report its conflict with `PartialEq`.
+    ord: Option<Path>,
+    /// The `Default` path of `#[derive(Default)]`, kept to report a missing default variant.
+    default: Option<Path>,
+    /// The variant built by the `Default` implementation, given with `#[default = VariantN]`.
+    default_variant: Option<Ident>,
+    /// Whether to generate a conversion from `Infallible`.
+    from_infallible: bool,
+    /// Whether to generate a struct with the kind of the wrapped error and the boxed error, instead
+    /// of an enum.
+    erased: bool,
+    /// Whether an `#[erased]` node caches the root cause of its source chain.
+    cache_root_cause: bool,
+    /// Whether to register the prefix of the node, so that a node with the same prefix in the same
+    /// module does not compile.
+    unique_prefix: bool,
+    /// Whether to capture a backtrace when the node is created, stored next to the wrapped error.
+    backtrace: bool,
+    /// The type of the context stored next to the wrapped error, given with `#[with_context(Type)]`.
+    context: Option<Type>,
+    /// Whether to generate a `<name>Result<T>` alias of `Result<T, <name>>`.
+    result: bool,
+    /// The other derives, emitted unchanged on the enum.
+    derives: Vec<Path>,
+    /// Attributes emitted unchanged on the enum.
+    passthrough: Vec<Attribute>,
+}
+
+impl ErrorNodeConfig {
+    fn from_attributes(attributes: &[Attribute]) -> syn::Result<Self> {
+        let mut config = ErrorNodeConfig::default();
+        for attribute in attributes {
+            if attribute.path().is_ident("display") {
+                attribute.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("cache_prefix") {
+                        config.cache_prefix = true;
+                        Ok(())
+                    } else if meta.path.is_ident("numbered") {
+                        config.numbered = true;
+                        Ok(())
+                    } else if meta.path.is_ident("transparent") {
+                        config.transparent = true;
+                        Ok(())
+                    } else if meta.path.is_ident("raw_prefix") {
+                        config.raw_prefix = true;
+                        Ok(())
+                    } else if meta.path.is_ident("separator") {
+                        config.separator = Some(meta.value()?.parse()?);
+                        Ok(())
+                    } else {
+                        Err(meta.error(expected_one_of(
+                            "unknown display option",
+                            &["cache_prefix", "numbered", "transparent", "raw_prefix", "separator"],
+                        )))
+                    }
+                })?;
+            } else if attribute.path().is_ident("source") {
+                attribute.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("collapse") {
+                        config.collapse_source = true;
+                        Ok(())
+                    } else {
+                        Err(meta.error(expected_one_of("unknown source option", &["collapse"])))
+                    }
+                })?;
+            } else if attribute.path().is_ident("debug") {
+                attribute.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("pretty") {
+                        config.pretty_debug = true;
+                        Ok(())
+                    } else {
+                        Err(meta.error(expected_one_of("unknown debug option", &["pretty"])))
+                    }
+                })?;
+            } else if attribute.path().is_ident("no_debug") {
+                attribute.meta.require_path_only()?;
+                config.no_debug = true;
+            } else if attribute.path().is_ident("derive") {
+                attribute.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("PartialEq") {
+                        config.partial_eq = true;
+                        Ok(())
+                    } else if meta.path.is_ident("Clone") {
+                        config.clone = true;
+                        Ok(())
+                    } else if meta.path.is_ident("Copy") {
+                        Err(meta.error("Copy cannot be derived, as every variant of a node wraps an error"))
+                    } else if meta.path.is_ident("PartialOrd") {
+                        config.partial_ord = Some(meta.path);
+                        Ok(())
+                    } else if meta.path.is_ident("Ord") {
+                        config.ord = Some(meta.path);
+                        Ok(())
+                    } else if meta.path.is_ident("Default") {
+                        config.default = Some(meta.path);
+                        Ok(())
+                    } else if meta.path.is_ident("Debug") {
+                        Err(meta.error(
+                            "Debug is always derived on error nodes, use #[no_debug] to implement it manually",
+                        ))
+                    } else {
+                        config.derives.push(meta.path);
+                        Ok(())
+                    }
+                })?;
+            } else if attribute.path().is_ident("doc") {
+                config.passthrough.push(attribute.clone());
+            } else if attribute.path().is_ident("kind_str") {
+                attribute.meta.require_path_only()?;
+                config.kind_str = true;
+            } else if attribute.path().is_ident("severity") {
+                attribute.meta.require_path_only()?;
+                config.severity = true;
+            } else if attribute.path().is_ident("log_line") {
+                attribute.meta.require_path_only()?;
+                config.log_line = true;
+            } else if attribute.path().is_ident("default") {
+                let value = &attribute.meta.require_name_value()?.value;
+                config.default_variant = Some(syn::parse2(value.to_token_stream())?);
+            } else if attribute.path().is_ident("from_infallible") {
+                attribute.meta.require_path_only()?;
+                config.from_infallible = true;
+            } else if attribute.path().is_ident("erased") {
+                attribute.meta.require_path_only()?;
+                config.erased = true;
+            } else if attribute.path().is_ident("cache_root_cause") {
+                attribute.meta.require_path_only()?;
+                config.cache_root_cause = true;
+            } else if attribute.path().is_ident("unique_prefix") {
+                attribute.meta.require_path_only()?;
+                config.unique_prefix = true;
+            } else if attribute.path().is_ident("backtrace") {
+                attribute.meta.require_path_only()?;
+                config.backtrace = true;
+            } else if attribute.path().is_ident("result") {
+                attribute.meta.require_path_only()?;
+                config.result = true;
+            } else if attribute.path().is_ident("with_context") {
+                config.context = Some(attribute.parse_args()?);
+            } else if attribute.path().is_ident("repr") {
+                config.repr = Some(attribute.parse_args()?);
+            } else {
+                return Err(SynError::new_spanned(
+                    attribute,
+                    expected_one_of("unsupported attribute on error node", NODE_ATTRIBUTES),
+                ));
+            }
+        }
+        Ok(config)
+    }
+}
+
+impl Parse for ErrorNode {
+    fn parse(input: syn::parse::ParseStream) -> syn::Result<Self> {
+        let attributes = input.call(Attribute::parse_outer)?;
+        let config = ErrorNodeConfig::from_attributes(&attributes)?;
+
+        let visibility: Visibility = input.parse()?;
+
+        let (node_name, generics, variants, message_prefix) = if input.peek(Token![type]) {
+            Self::parse_angle_bracket_form(input)?
+        } else {
+            Self::parse_brace_form(input)?
+        };
+
+        Self::check_accepted_types(&variants)?;
+        Self::check_variant_names(&variants)?;
+        Self::check_catch_all(&variants)?;
+
+        if let (Some(partial_ord), None) = (&config.partial_ord, &config.ord) {
+            return Err(SynError::new_spanned(
+                partial_ord,
+                "PartialOrd can only be derived together with Ord, which orders the nodes by variant",
+            ));
+        }
+
+        if let (Some(ord), true) = (&config.ord, config.partial_eq) {
+            return Err(SynError::new_spanned(
+                ord,
+                "derive(Ord) compares the variants only, so it cannot be used with derive(PartialEq), \
+                 which compares the wrapped errors",
+            ));
+        }
+
+        match (&config.default, &config.default_variant) {
+            (Some(default), None) => {
+                return Err(SynError::new_spanned(
+                    default,
+                    "derive(Default) requires the variant to build, given with #[default = VariantN]",
+                ));
+            }
+            (None, Some(default_variant)) => {
+                return Err(SynError::new_spanned(
+                    default_variant,
+                    "#[default = VariantN] requires #[derive(Default)]",
+                ));
+            }
+            (Some(_), Some(default_variant))
+                if !variants.iter().enumerate().any(|(index, variant)| variant.name(index) == *default_variant) =>
+            {
+                return Err(SynError::new_spanned(
+                    default_variant,
+                    format!("the node has no variant named '{}'", default_variant),
+                ));
+            }
+            _ => {}
+        }
+
+        if config.repr.is_none() {
+            if let Some(discriminant) = variants.iter().find_map(|it| it.discriminant.as_ref()) {
+                return Err(SynError::new_spanned(
+                    discriminant,
+                    "explicit discriminants require a #[repr(...)] attribute on the node",
+                ));
+            }
+        }
+
+        if config.transparent
+            && (config.numbered || message_prefix.is_some() || variants.iter().any(|it| it.config.prefix.is_some()))
+        {
+            return Err(SynError::new(
+                node_name.span(),
+                "display(transparent) cannot be used with a prefix nor with display(numbered)",
+            ));
+        }
+
+        if let Some(separator) = &config.separator {
+            if config.raw_prefix || config.transparent {
+                return Err(SynError::new_spanned(
+                    separator,
+                    "display(separator) cannot be used with display(raw_prefix) nor display(transparent)",
+                ));
+            }
+        }
+
+        if config.no_debug && config.pretty_debug {
+            return Err(SynError::new(node_name.span(), "#[no_debug] cannot be used with #[debug(pretty)]"));
+        }
+
+        if config.collapse_source && !config.transparent {
+            return Err(SynError::new(node_name.span(), "source(collapse) requires display(transparent)"));
+        }
+
+        if let Some(MessagePrefix::Literal(prefix)) = &message_prefix {
+            Self::check_literal_prefix(prefix)?;
+        }
+
+        if config.cache_prefix && !matches!(message_prefix, Some(MessagePrefix::Expr(_))) {
+            return Err(SynError::new(
+                node_name.span(),
+                "display(cache_prefix) requires a prefix given as an expression",
+            ));
+        }
+
+        if config.unique_prefix && !matches!(message_prefix, Some(MessagePrefix::Literal(_))) {
+            return Err(SynError::new(
+                node_name.span(),
+                "#[unique_prefix] requires a prefix given as a string literal",
+            ));
+        }
+
+        if config.backtrace {
+            let unsupported_derive = if config.partial_eq {
+                Some("PartialEq")
+            } else if config.clone {
+                Some("Clone")
+            } else {
+                None
+            };
+            if let Some(derive) = unsupported_derive {
+                return Err(SynError::new(
+                    node_name.span(),
+                    format!("#[backtrace] cannot be used with derive({}), as a backtrace does not implement it", derive),
+                ));
+            }
+        }
+
+        if config.result {
+            if let Some(param) = generics.type_params().find(|it| it.ident == "T") {
+                return Err(SynError::new_spanned(
+                    param,
+                    "#[result] declares its own `T` parameter, so the node cannot have a generic parameter named T",
+                ));
+            }
+        }
+
+        if config.cache_root_cause && !config.erased {
+            return Err(SynError::new(
+                node_name.span(),
+                "#[cache_root_cause] is only supported on #[erased] nodes",
+            ));
+        }
+
+        if config.context.is_some() {
+            if config.default_variant.is_some() {
+                return Err(SynError::new(
+                    node_name.span(),
+                    "#[with_context] cannot be used with #[default = ...], as the context has no default value",
+                ));
+            }
+            if let Some(variant) = variants.iter().find(|it| it.config.guard.is_some()) {
+                return Err(SynError::new_spanned(
+                    &variant.path,
+                    "the variants of a #[with_context] node cannot have a guard, as the guarded constructor takes no context",
+                ));
+            }
+        }
+
+        if config.erased {
+            Self::check_erased(&config, &node_name, &generics, &variants)?;
+        }
+
+        Ok(ErrorNode {
+            config,
+            visibility,
+            node_name,
+            generics,
+            variants,
+            message_prefix,
+            common_prefix: None,
+        })
+    }
+}
+
+impl ErrorNode {
+    /// Parses the body of the node in the form `type (name)[<generics>]<variants> [where (predicates)] [= (prefix)]`.
+    fn parse_angle_bracket_form(input: syn::parse::ParseStream) -> syn::Result<ErrorNodeBody> {
+        let _: Token![type] = input.parse()?;
+        let node_name: Ident = input.parse()?;
+
+        // Generic parameters are declared in a first angle-bracketed list, followed by the one of
+        // the variants.
+        let fork = input.fork();
+        let mut generics = match fork.parse::<Generics>() {
+            Ok(_) if fork.peek(Token![<]) => input.parse::<Generics>()?,
+            _ => Generics::default(),
+        };
+
+        let mut variants: Vec<ErrorNodeVariant> = vec![];
+        let _open_angle_bracket: Token![<] = input.parse()?;
+        let mut keep_parsing_variants = true;
+        while keep_parsing_variants {
+            if input.lookahead1().peek(Token![>]) {
+                keep_parsing_variants = false;
+                let _close_angle_bracket: Token![>] = input.parse()?;
+            } else {
+                variants.push(input.parse()?);
+                if input.lookahead1().peek(Token![,]) {
+                    let _: Token![,] = input.parse()?;
+                }
+            }
+        }
+
+        if input.peek(Token![where]) {
+            generics.where_clause = Some(input.parse()?);
+        }
+
+        let message_prefix = if input.peek(Token![=]) {
+            let _: Token![=] = input.parse()?;
+            if input.peek(LitStr) {
+                Some(MessagePrefix::Literal(input.parse()?))
+            } else {
+                Some(MessagePrefix::Expr(input.parse()?))
+            }
+        } else {
+            None
+        };
+
+        Ok((node_name, generics, variants, message_prefix))
+    }
+
+    /// Parses the body of the node in the form `(name)[<generics>] [= (prefix)] { variants }`.
+    fn parse_brace_form(input: syn::parse::ParseStream) -> syn::Result<ErrorNodeBody> {
+        let node_name: Ident = input.parse()?;
+        let generics = if input.peek(Token![<]) {
+            input.parse::<Generics>()?
+        } else {
+            Generics::default()
+        };
+
+        let message_prefix = if input.peek(Token![=]) {
+            let _: Token![=] = input.parse()?;
+            if input.peek(LitStr) {
+                Some(MessagePrefix::Literal(input.parse()?))
+            } else {
+                Some(MessagePrefix::Expr(Expr::parse_without_eager_brace(input)?))
+            }
+        } else {
+            None
+        };
+
+        let content;
+        braced!(content in input);
+        let variants = content
+            .parse_terminated(ErrorNodeVariant::parse, Token![,])?
+            .into_iter()
+            .collect();
+
+        Ok((node_name, generics, variants, message_prefix))
+    }
+
+    /// Checks that no two variants have the same name, including the `Variant<index>` names of the
+    /// unnamed ones.
+    fn check_variant_names(variants: &[ErrorNodeVariant]) -> syn::Result<()> {
+        let names: Vec<Ident> = variants.iter().enumerate().map(|(index, variant)| variant.name(index)).collect();
+        for (index, name) in names.iter().enumerate() {
+            if names[..index].contains(name) {
+                return Err(SynError::new_spanned(
+                    &variants[index].path,
+                    format!("the node already has a variant named '{}'", name),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the catch-all variant, declared with `..`, is the last one.
+    fn check_catch_all(variants: &[ErrorNodeVariant]) -> syn::Result<()> {
+        match variants.iter().rev().skip(1).find_map(|it| it.catch_all.as_ref()) {
+            Some(catch_all) => Err(SynError::new_spanned(catch_all, "the catch-all variant `..` must be the last one")),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks that no type is wrapped or accepted by more than one variant, nor accepted by a variant
+    /// while being wrapped by another one, as the generated `From` implementations would conflict.
+    fn check_accepted_types(variants: &[ErrorNodeVariant]) -> syn::Result<()> {
+        let mut converted_types: Vec<(String, usize)> = vec![];
+        for (index, variant) in variants.iter().enumerate().filter(|(_, variant)| !variant.config.no_from) {
+            let wrapped_str = variant.path.to_token_stream().to_string();
+            if let Some((_, other_index)) = converted_types.iter().find(|(it, _)| *it == wrapped_str) {
+                return Err(SynError::new_spanned(
+                    &variant.path,
+                    format!(
+                        "`{}` is wrapped by both {} and {}: their From implementations would conflict, \
+                         mark one of them with #[no_from]",
+                        wrapped_str.replace(' ', ""),
+                        variants[*other_index].name(*other_index),
+                        variant.name(index),
+                    ),
+                ));
+            }
+            converted_types.push((wrapped_str, index));
+        }
+        for (index, variant) in variants.iter().enumerate() {
+            for accepted in &variant.config.accepts {
+                let accepted_str = accepted.to_token_stream().to_string();
+                if let Some((_, other_index)) = converted_types.iter().find(|(it, _)| *it == accepted_str) {
+                    return Err(SynError::new_spanned(
+                        accepted,
+                        format!(
+                            "`{}` is converted into both {} and {}: the conversion would be ambiguous",
+                            accepted_str.replace(' ', ""),
+                            variants[*other_index].name(*other_index),
+                            variant.name(index),
+                        ),
+                    ));
+                }
+                converted_types.push((accepted_str, index));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that an `#[erased]` node uses only the options which do not depend on the variants of
+    /// an enum.
+    fn check_erased(
+        config: &ErrorNodeConfig,
+        node_name: &Ident,
+        generics: &Generics,
+        variants: &[ErrorNodeVariant],
+    ) -> syn::Result<()> {
+        let unsupported_options = [
+            (config.transparent, "display(transparent)"),
+            (config.collapse_source, "source(collapse)"),
+            (config.repr.is_some(), "#[repr(...)]"),
+            (config.pretty_debug, "#[debug(pretty)]"),
+            (config.kind_str, "#[kind_str]"),
+            (config.severity, "#[severity]"),
+            (config.log_line, "#[log_line]"),
+            (config.partial_eq, "derive(PartialEq)"),
+            (config.clone, "derive(Clone)"),
+            (config.ord.is_some(), "derive(Ord)"),
+            (config.default.is_some(), "derive(Default)"),
+            (!config.derives.is_empty(), "other derives"),
+            (config.backtrace, "#[backtrace]"),
+            (config.context.is_some(), "#[with_context(...)]"),
+        ];
+        if let Some((_, option)) = unsupported_options.iter().find(|(is_set, _)| *is_set) {
+            return Err(SynError::new(
+                node_name.span(),
+                format!("#[erased] cannot be used with {}", option),
+            ));
+        }
+        if !generics.params.is_empty() {
+            return Err(SynError::new_spanned(&generics.params, "an #[erased] node cannot be generic"));
+        }
+        if let Some(variant) = variants
+            .iter()
+            .find(|it| !it.config.is_empty() || !it.lifted.is_empty() || it.discriminant.is_some())
+        {
+            return Err(SynError::new_spanned(
+                &variant.path,
+                "the variants of an #[erased] node cannot have attributes, lifted errors nor discriminants",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Returns the separator between the prefix and the source of the node, as a part of the format
+    /// string of the node message and its arguments.
+    fn separator(&self) -> (String, TokenStream2) {
+        if self.config.raw_prefix {
+            (String::from(" "), TokenStream2::new())
+        } else if let Some(separator) = &self.config.separator {
+            (separator.value().replace('{', "{{").replace('}', "}}"), TokenStream2::new())
+        } else {
+            let separator = default_node_separator();
+            (String::from("{}"), quote! { #separator, })
+        }
+    }
+
+    /// Prepends `common_prefix` to the prefix of the node (or to its name, if it has no prefix),
+    /// separated by the default separator.
+    fn scope_prefix(&mut self, common_prefix: &LitStr) {
+        let separator = default_node_separator();
+        match self.message_prefix.take() {
+            Some(MessagePrefix::Expr(prefix)) => {
+                self.message_prefix =
+                    Some(MessagePrefix::Expr(parse_quote! { format!("{}{}{}", #common_prefix, #separator, #prefix) }));
+            }
+            message_prefix => {
+                self.message_prefix = message_prefix;
+                self.common_prefix = Some(common_prefix.clone());
+            }
+        }
+        for variant in &mut self.variants {
+            if let Some(prefix) = variant.config.prefix.take() {
+                variant.config.prefix = Some(parse_quote! { format!("{}{}{}", #common_prefix, #separator, #prefix) });
+            }
+        }
+    }
+
+    /// Checks that a literal prefix has no format placeholders, as it is embedded in the format
+    /// string of the node message. Escaped braces (`{{` and `}}`) are accepted.
+    fn check_literal_prefix(prefix: &LitStr) -> syn::Result<()> {
+        let value = prefix.value();
+        let mut chars = value.chars();
+        while let Some(c) = chars.next() {
+            if (c == '{' || c == '}') && chars.next() != Some(c) {
+                return Err(SynError::new_spanned(
+                    prefix,
+                    "a literal prefix cannot contain format placeholders: escape braces as `{{` and `}}`, \
+                     or give the prefix as an expression, like `format!(...)`",
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn to_token_stream(&self) -> TokenStream {
+        let mut token_buffer = TokenStream::from(self.unique_prefix_const());
+        token_buffer.extend(TokenStream::from(self.result_alias()));
+        if self.config.erased {
+            token_buffer.extend(self.erased_error_node());
+            return token_buffer;
+        }
+        let enum_declaration = self.error_node_enum();
+        let impl_display = self.error_node_display_impl();
+        let impl_error = self.error_node_error_impl();
+        let impl_froms = self.error_node_from_impls();
+        let impl_inherent = self.error_node_inherent_impl();
+        let impl_debug = self.error_node_debug_impl();
+        let kind = self.error_node_kind();
+
+        token_buffer.extend(enum_declaration);
+        token_buffer.extend(impl_display);
+        token_buffer.extend(impl_debug);
+        token_buffer.extend(impl_error);
+        token_buffer.extend(impl_froms);
+        token_buffer.extend(impl_inherent);
+        token_buffer.extend(TokenStream::from(self.error_node_json()));
+        token_buffer.extend(TokenStream::from(self.retryable_impls()));
+        token_buffer.extend(TokenStream::from(self.coded_impls()));
+        token_buffer.extend(TokenStream::from(self.severity_impls()));
+        token_buffer.extend(kind);
+        token_buffer
+    }
+
+    fn error_node_enum(&self) -> TokenStream {
+        let mut token_buffer = TokenStream2::new();
+        for attribute in &self.config.passthrough {
+            token_buffer.extend(attribute.to_token_stream());
+        }
+        if !self.config.pretty_debug && !self.config.no_debug {
+            token_buffer.extend(quote! { #[derive(Debug)] });
+        }
+        if self.config.partial_eq {
+            token_buffer.extend(quote! { #[derive(PartialEq)] });
+        }
+        if !self.config.derives.is_empty() {
+            let derives = &self.config.derives;
+            token_buffer.extend(quote! { #[derive(#( #derives ),*)] });
+        }
+        if self.config.clone {
+            token_buffer.extend(quote! { #[derive(Clone)] });
+        }
+        token_buffer.extend(self.visibility.to_token_stream());
+        token_buffer.extend(quote! { enum });
+        token_buffer.extend(self.node_name.clone().into_token_stream());
+        token_buffer.extend(self.generics.to_token_stream());
+        token_buffer.extend(self.generics.where_clause.to_token_stream());
+        let context_type = match &self.config.context {
+            Some(context) => quote! { , #context },
+            None => TokenStream2::new(),
+        };
+        let backtrace_type = if self.config.backtrace {
+            quote! { , std::backtrace::Backtrace }
+        } else {
+            TokenStream2::new()
+        };
+        token_buffer.extend(
+            Group::new(
+                proc_macro2::Delimiter::Brace,
+                TokenStream2::from_iter(self.variants.iter().enumerate().map(|it| {
+                    let variant_ident = it.1.name(it.0);
+                    let variant_field_type = it.1.field_type();
+                    let variant_doc = format!(
+                        "Wraps a `{}`{}.",
+                        it.1.type_name(),
+                        if it.1.config.arc { ", shared with an `Arc`" } else { "" }
+                    );
+                    quote! {
+                        #[doc = #variant_doc]
+                        #variant_ident(#variant_field_type #context_type #backtrace_type),
+                    }
+                })),
+            )
+                .to_token_stream(),
+        );
+        token_buffer.extend(self.variant_types_const());
+        token_buffer.extend(self.trait_object_assertion());
+        if self.config.ord.is_some() {
+            token_buffer.extend(self.variant_order_impls());
+        }
+        token_buffer.extend(self.default_impl());
+        if self.config.partial_eq {
+            token_buffer.extend(self.partial_eq_assertion());
+        }
+        if self.config.no_debug && self.generics.params.is_empty() {
+            token_buffer.extend(manual_debug_assertion(&self.node_name, "#[no_debug]"));
+        }
+        token_buffer.into()
+    }
+
+    /// Generates the `PartialEq`, `Eq`, `PartialOrd` and `Ord` implementations of `#[derive(Ord)]`
+    /// nodes, which compare the index of their variants only, ignoring the wrapped errors.
+    fn variant_order_impls(&self) -> TokenStream2 {
+        let node_name = &self.node_name;
+        let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+        let index_matches = self.variants.iter().enumerate().map(|(index, variant)| {
+            let variant_name = variant.name(index);
+            quote! { Self::#variant_name(..) => #index, }
+        });
+        quote! {
+            impl #impl_generics #node_name #ty_generics #where_clause {
+                fn variant_index(&self) -> usize {
+                    match self {
+                        #( #index_matches )*
+                    }
+                }
+            }
+
+            impl #impl_generics PartialEq for #node_name #ty_generics #where_clause {
+                fn eq(&self, other: &Self) -> bool {
+                    self.variant_index() == other.variant_index()
+                }
+            }
+
+            impl #impl_generics Eq for #node_name #ty_generics #where_clause {}
+
+            impl #impl_generics PartialOrd for #node_name #ty_generics #where_clause {
+                fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+                    Some(self.cmp(other))
+                }
+            }
+
+            impl #impl_generics Ord for #node_name #ty_generics #where_clause {
+                fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+                    self.variant_index().cmp(&other.variant_index())
+                }
+            }
+        }
+    }
+
+    /// Generates the `Default` implementation of `#[derive(Default)]` nodes, which wraps the default
+    /// value of the error of the variant given with `#[default = VariantN]`.
+    fn default_impl(&self) -> TokenStream2 {
+        let Some(default_variant) = &self.config.default_variant else {
+            return TokenStream2::new();
+        };
+        let Some(variant) = self
+            .variants
+            .iter()
+            .enumerate()
+            .find_map(|(index, variant)| (variant.name(index) == *default_variant).then_some(variant))
+        else {
+            return TokenStream2::new();
+        };
+        let node_name = &self.node_name;
+        let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+        let variant_inner_type = &variant.path;
+        let backtrace_field = self.backtrace_field();
+        let default_value = variant.wrap_tokens(
+            quote_spanned! { variant_inner_type.span() => <#variant_inner_type as Default>::default() },
+        );
+        quote! {
+            impl #impl_generics Default for #node_name #ty_generics #where_clause {
+                fn default() -> Self {
+                    Self::#default_variant(#default_value #backtrace_field)
+                }
+            }
+        }
+    }
+
+    /// Returns the backtrace captured along with the wrapped error when a `#[backtrace]` node is
+    /// created, as the second field of the variant.
+    fn backtrace_field(&self) -> TokenStream2 {
+        if self.config.backtrace {
+            quote! { , std::backtrace::Backtrace::capture() }
+        } else {
+            TokenStream2::new()
+        }
+    }
+
+    /// Generates the `backtrace` method of `#[backtrace]` nodes.
+    fn backtrace_method(&self) -> TokenStream2 {
+        if !self.config.backtrace {
+            return TokenStream2::new();
+        }
+        let backtrace_matches = self.variants.iter().enumerate().map(|(index, variant)| {
+            let variant_name = variant.name(index);
+            quote! { Self::#variant_name(_, .., backtrace) => backtrace, }
+        });
+        quote! {
+            /// Returns the backtrace captured when this node was created from the wrapped error.
+            pub fn backtrace(&self) -> &std::backtrace::Backtrace {
+                match self {
+                    #( #backtrace_matches )*
+                }
+            }
+        }
+    }
+
+    /// Generates the `provide` method of the `Error` implementation of `#[backtrace]` nodes, which
+    /// provides the captured backtrace. It is emitted only when `hierrorchy` is built with
+    /// `--cfg hierrorchy_provide`, as `Error::provide` is unstable.
+    fn provide_method(&self) -> TokenStream2 {
+        if !self.config.backtrace {
+            return TokenStream2::new();
+        }
+        quote! {
+            ::hierrorchy::__private::with_provide! {
+                fn provide<'a>(&'a self, request: &mut std::error::Request<'a>) {
+                    request.provide_ref::<std::backtrace::Backtrace>(self.backtrace());
+                }
+            }
+        }
+    }
+
+    /// Generates the `context` method of `#[with_context(Type)]` nodes.
+    fn context_method(&self) -> TokenStream2 {
+        let Some(context) = &self.config.context else {
+            return TokenStream2::new();
+        };
+        let context_matches = self.variants.iter().enumerate().map(|(index, variant)| {
+            let variant_name = variant.name(index);
+            quote! { Self::#variant_name(_, context, ..) => context, }
+        });
+        quote! {
+            /// Returns the context given when this node was created from the wrapped error.
+            pub fn context(&self) -> &#context {
+                match self {
+                    #( #context_matches )*
+                }
+            }
+        }
+    }
+
+    /// Generates a constant named after the prefix of a `#[unique_prefix]` node, so that two nodes
+    /// with the same prefix in the same module fail with a duplicate definition. The characters of
+    /// the prefix which are not ASCII alphanumeric are escaped as `_<hex code>_`, and the common
+    /// prefix of the nodes declared with [`error_nodes`](crate::error_nodes) is separated from the
+    /// prefix by `__`.
+    fn unique_prefix_const(&self) -> TokenStream2 {
+        let Some(MessagePrefix::Literal(prefix)) = self.message_prefix.as_ref().filter(|_| self.config.unique_prefix)
+        else {
+            return TokenStream2::new();
+        };
+        let mut const_name = String::from("__HIERRORCHY_UNIQUE_PREFIX_");
+        let escape = |const_name: &mut String, value: String| {
+            for c in value.chars() {
+                if c.is_ascii_alphanumeric() {
+                    const_name.push(c);
+                } else {
+                    const_name.push_str(&format!("_{:X}_", c as u32));
+                }
+            }
+        };
+        if let Some(common_prefix) = &self.common_prefix {
+            escape(&mut const_name, common_prefix.value());
+            const_name.push_str("__");
+        }
+        escape(&mut const_name, prefix.value());
+        let const_name = Ident::new(&const_name, prefix.span());
+        quote! {
+            #[doc(hidden)]
+            #[allow(non_upper_case_globals, dead_code)]
+            const #const_name: () = ();
+        }
+    }
+
+    /// Generates the `<name>Result<T>` alias of `#[result]` nodes. The bounds of the generic
+    /// parameters of the node are not repeated, as they are not enforced on type aliases.
+    fn result_alias(&self) -> TokenStream2 {
+        if !self.config.result {
+            return TokenStream2::new();
+        }
+        let node_name = &self.node_name;
+        let alias_name = format_ident!("{}Result", node_name);
+        let alias_doc = format!("A [Result] whose error is a [{}].", node_name);
+        let vis = &self.visibility;
+        let lifetimes = self.generics.lifetimes().map(|it| &it.lifetime);
+        let type_params = self.generics.type_params().map(|it| &it.ident);
+        let const_params = self.generics.const_params().map(|it| {
+            let ident = &it.ident;
+            let ty = &it.ty;
+            quote! { const #ident: #ty }
+        });
+        let (_, ty_generics, _) = self.generics.split_for_impl();
+        quote! {
+            #[doc = #alias_doc]
+            #vis type #alias_name<#( #lifetimes, )* T, #( #type_params, )* #( #const_params, )*> = std::result::Result<T, #node_name #ty_generics>;
+        }
+    }
+
+    /// Generates a static assertion that the trait of each variant wrapping a `Box<dyn Trait>` has
+    /// `Error` as a supertrait, so that a missing bound is reported on the declaration of the
+    /// variant.
+    fn trait_object_assertion(&self) -> TokenStream2 {
+        let assertions: Vec<TokenStream2> = self
+            .variants
+            .iter()
+            .filter_map(ErrorNodeVariant::boxed_trait_object)
+            .map(|trait_object| {
+                quote_spanned! { trait_object.span() => assert_error::<#trait_object>(); }
+            })
+            .collect();
+        if assertions.is_empty() {
+            return TokenStream2::new();
+        }
+        quote! {
+            const _: () = {
+                fn assert_error<T: std::error::Error + ?Sized>() {}
+
+                #[allow(dead_code)]
+                fn assert_trait_objects_error() {
+                    #( #assertions )*
+                }
+            };
+        }
+    }
+
+    /// Generates the `VARIANT_TYPES` associated constant, with the name of the type wrapped by each
+    /// variant.
+    fn variant_types_const(&self) -> TokenStream2 {
+        let node_name = &self.node_name;
+        let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+        let variant_types = self.variants.iter().map(ErrorNodeVariant::type_name);
+        quote! {
+            impl #impl_generics #node_name #ty_generics #where_clause {
+                /// The name of the error type wrapped by each variant, in declaration order.
+                pub const VARIANT_TYPES: &'static [&'static str] = &[#( #variant_types ),*];
+            }
+        }
+    }
+
+    /// Generates a static assertion that the error of each variant is `PartialEq`, so that a missing
+    /// implementation is reported on the declaration of the variant.
+    fn partial_eq_assertion(&self) -> TokenStream2 {
+        let mut generics = self.generics.clone();
+        for param in generics.type_params_mut() {
+            param.bounds.push(parse_quote!(PartialEq));
+        }
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        let assertions = self.variants.iter().map(|variant| {
+            let variant_inner_type = &variant.path;
+            quote_spanned! { variant_inner_type.span() => assert_partial_eq::<#variant_inner_type>(); }
+        });
+        quote! {
+            const _: () = {
+                fn assert_partial_eq<T: PartialEq>() {}
+
+                #[allow(dead_code)]
+                fn assert_variants_partial_eq #impl_generics () #where_clause {
+                    #( #assertions )*
+                }
+            };
+        }
+    }
+
+    fn error_node_display_impl(&self) -> TokenStream {
+        let mut token_buffer = TokenStream2::new();
+        let node_name = &self.node_name;
+        let generics = self.static_generics();
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        token_buffer.extend(quote! { impl #impl_generics std::fmt::Display for #node_name #ty_generics #where_clause });
+        // The error written after the prefix, matched directly so that printing cannot panic. It only
+        // needs to be an error for numbered messages, which print its source chain.
+        let displayed_error_type = if self.config.numbered {
+            quote! { &(dyn std::error::Error + 'static) }
+        } else {
+            quote! { &dyn std::fmt::Display }
+        };
+        let displayed_error_value = if self.config.erased {
+            quote! { self.inner() }
+        } else {
+            let error_matches = self.variants.iter().enumerate().map(|(index, variant)| {
+                let variant_name = variant.name(index);
+                let variant_error = variant.error_tokens();
+                quote! { Self::#variant_name(err, ..) => #variant_error, }
+            });
+            quote! {
+                match self {
+                    #( #error_matches )*
+                }
+            }
+        };
+        let displayed_error = quote! {
+            {
+                let displayed_error: #displayed_error_type = #displayed_error_value;
+                displayed_error
+            }
+        };
+        // The statements computing the prefix, the prefix part of the format string and its arguments.
+        let (prefix_setup, prefix_format, prefix_args) = match &self.message_prefix {
+            Some(MessagePrefix::Expr(prefix)) if self.config.cache_prefix => (
+                quote! {
+                    static PREFIX: std::sync::OnceLock<String> = std::sync::OnceLock::new();
+                    let prefix = PREFIX.get_or_init(|| (#prefix).to_string());
+                },
+                String::from("{}"),
+                quote! { prefix, },
+            ),
+            Some(MessagePrefix::Expr(prefix)) => (TokenStream2::new(), String::from("{}"), quote! { #prefix, }),
+            Some(MessagePrefix::Literal(prefix)) => (TokenStream2::new(), prefix.value(), TokenStream2::new()),
+            None => (TokenStream2::new(), node_name.to_string(), TokenStream2::new()),
+        };
+        let (prefix_format, prefix_args) = match &self.common_prefix {
+            Some(common_prefix) => {
+                let separator = default_node_separator();
+                (format!("{}{{}}{}", common_prefix.value(), prefix_format), quote! { #separator, #prefix_args })
+            }
+            None => (prefix_format, prefix_args),
+        };
+        let (separator_format, separator_args) = self.separator();
+        let write_message = if self.config.transparent {
+            let variant_matches = self.variants.iter().enumerate().map(|(index, variant)| {
+                let variant_name = variant.name(index);
+                let variant_error = variant.error_tokens();
+                quote! { Self::#variant_name(err, ..) => std::fmt::Display::fmt(#variant_error, f), }
+            });
+            quote! {
+                match self {
+                    #( #variant_matches )*
+                }
+            }
+        } else {
+            // The numbered chain of the source stops at terminal variants, which have no source, so
+            // that the numbers match the source chain.
+            let write_numbered_source = if self.variants.iter().any(|it| it.config.terminal) {
+                let terminal_matches = self.variants.iter().enumerate().filter(|it| it.1.config.terminal).map(
+                    |(index, variant)| {
+                        let variant_name = variant.name(index);
+                        let variant_error = variant.error_tokens();
+                        quote! { Self::#variant_name(err, ..) => write!(f, "[2] {}", #variant_error), }
+                    },
+                );
+                quote! {
+                    match self {
+                        #( #terminal_matches )*
+                        #[allow(unreachable_patterns)]
+                        _ => ::hierrorchy::write_numbered_chain(f, #displayed_error, 2),
+                    }
+                }
+            } else {
+                quote! { ::hierrorchy::write_numbered_chain(f, #displayed_error, 2) }
+            };
+            // The statements writing the prefix and the source.
+            let write_prefixed = |prefix_setup: TokenStream2, prefix_format: &str, prefix_args: TokenStream2| {
+                if self.config.numbered {
+                    let message_format = format!("[1] {}{}", prefix_format, separator_format);
+                    quote! {
+                        #prefix_setup
+                        write!(f, #message_format, #prefix_args #separator_args)?;
+                        #write_numbered_source
+                    }
+                } else {
+                    let message_format = format!("{}{}{{}}", prefix_format, separator_format);
+                    quote! {
+                        #prefix_setup
+                        write!(f, #message_format, #prefix_args #separator_args #displayed_error)
+                    }
+                }
+            };
+            let write_node_prefixed = write_prefixed(prefix_setup, &prefix_format, prefix_args);
+            if self.variants.iter().any(|it| it.config.prefix.is_some()) {
+                let prefix_matches = self.variants.iter().enumerate().filter_map(|(index, variant)| {
+                    let prefix = variant.config.prefix.as_ref()?;
+                    let variant_name = variant.name(index);
+                    let write_variant_prefixed = write_prefixed(TokenStream2::new(), "{}", quote! { #prefix, });
+                    Some(quote! { Self::#variant_name(..) => { #write_variant_prefixed } })
+                });
+                quote! {
+                    match self {
+                        #( #prefix_matches )*
+                        #[allow(unreachable_patterns)]
+                        _ => { #write_node_prefixed }
+                    }
+                }
+            } else {
+                write_node_prefixed
+            }
+        };
+        let write_message = if self.variants.iter().any(|it| it.config.display.is_some()) {
+            let display_matches = self.variants.iter().enumerate().filter_map(|(index, variant)| {
+                let display = variant.config.display.as_ref()?;
+                let variant_name = variant.name(index);
+                let variant_inner_type = &variant.path;
+                let variant_value = variant.declared_value_tokens();
+                Some(quote! {
+                    Self::#variant_name(err, ..) => {
+                        let display: fn(&#variant_inner_type, &mut std::fmt::Formatter<'_>) -> std::fmt::Result = #display;
+                        display(#variant_value, f)
+                    }
+                })
+            });
+            quote! {
+                match self {
+                    #( #display_matches )*
+                    #[allow(unreachable_patterns)]
+                    _ => { #write_message }
+                }
+            }
+        } else {
+            write_message
+        };
+        let write_suffix = if self.variants.iter().any(|it| it.config.suffix.is_some()) {
+            let suffix_matches = self.variants.iter().enumerate().map(|(index, variant)| {
+                let variant_name = variant.name(index);
+                match &variant.config.suffix {
+                    Some(suffix) => quote! { Self::#variant_name(..) => f.write_str(#suffix), },
+                    None => quote! { Self::#variant_name(..) => Ok(()), },
+                }
+            });
+            quote! {
+                ?;
+                match self {
+                    #( #suffix_matches )*
+                }
+            }
+        } else {
+            TokenStream2::new()
+        };
+        token_buffer.extend(
+            Group::new(
+                proc_macro2::Delimiter::Brace,
+                quote! {
+                    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        #write_message
+                        #write_suffix
+                    }
+                },
+            )
+                .to_token_stream(),
+        );
+        token_buffer.into()
+    }
+
+    /// Generates the `Debug` implementation for `#[debug(pretty)]` nodes, which prints the variant
+    /// qualified with the node name and, with the alternate flag, the wrapped error on its own
+    /// indented lines.
+    fn error_node_debug_impl(&self) -> TokenStream {
+        if !self.config.pretty_debug {
+            return TokenStream::new();
+        }
+        let node_name = &self.node_name;
+        let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+        let variant_matches = self.variants.iter().enumerate().map(|(index, variant)| {
+            let variant_name = variant.name(index);
+            let qualified_name = format!("{}::{}", node_name, variant_name);
+            quote! {
+                Self::#variant_name(err, ..) => f.debug_tuple(#qualified_name).field(err).finish(),
+            }
+        });
+        quote! {
+            impl #impl_generics std::fmt::Debug for #node_name #ty_generics #where_clause {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    match self {
+                        #( #variant_matches )*
+                    }
+                }
+            }
+        }
+        .into()
+    }
+
+    fn error_node_error_impl(&self) -> TokenStream {
+        let mut token_buffer = TokenStream2::new();
+        let node_name = &self.node_name;
+        let generics = self.static_generics();
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        token_buffer.extend(quote! { impl #impl_generics std::error::Error for #node_name #ty_generics #where_clause });
+        let provide_method = self.provide_method();
+        let variant_matches = TokenStream2::from_iter(self.variants.iter().enumerate().map(|it| {
+            let variant_name = it.1.name(it.0);
+            let variant_error = it.1.error_tokens();
+            if it.1.config.terminal {
+                quote! {
+                    Self::#variant_name(..) => None,
+                }
+            } else if self.config.collapse_source {
+                quote! {
+                    Self::#variant_name(err, ..) => std::error::Error::source(#variant_error),
+                }
+            } else {
+                quote! {
+                    Self::#variant_name(err, ..) => Some(#variant_error),
+                }
+            }
+        }));
+        token_buffer.extend(
+            Group::new(
+                proc_macro2::Delimiter::Brace,
+                quote! {
+                    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                        match self {
+                            #variant_matches
+                        }
+                    }
+
+                    #provide_method
+                },
+            )
+                .to_token_stream(),
+        );
+        token_buffer.into()
+    }
+
+    /// Generates the conversion from `Infallible` of `#[from_infallible]` nodes, so that the `?`
+    /// operator can be used on results which cannot fail.
+    fn infallible_from_impl(&self) -> TokenStream2 {
+        if !self.config.from_infallible {
+            return TokenStream2::new();
+        }
+        let node_name = &self.node_name;
+        let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+        quote! {
+            impl #impl_generics From<std::convert::Infallible> for #node_name #ty_generics #where_clause {
+                fn from(value: std::convert::Infallible) -> Self {
+                    match value {}
+                }
+            }
+        }
+    }
+
+    /// Generates a conversion from `source_type` into the given variant, built from the converted
+    /// `value`. The conversion of a `#[with_context(Type)]` node takes a `(context, value)` tuple
+    /// instead.
+    fn variant_from_impl(&self, variant_name: &Ident, source_type: TokenStream2, variant_value: TokenStream2) -> TokenStream2 {
+        let node_name = &self.node_name;
+        let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+        let backtrace_field = self.backtrace_field();
+        match &self.config.context {
+            Some(context) => quote! {
+                impl #impl_generics From<(#context, #source_type)> for #node_name #ty_generics #where_clause {
+                    fn from((context, value): (#context, #source_type)) -> Self {
+                        Self::#variant_name(#variant_value, context #backtrace_field)
+                    }
+                }
+            },
+            None => quote! {
+                impl #impl_generics From<#source_type> for #node_name #ty_generics #where_clause {
+                    fn from(value: #source_type) -> Self {
+                        Self::#variant_name(#variant_value #backtrace_field)
+                    }
+                }
+            },
+        }
+    }
+
+    fn error_node_from_impls(&self) -> TokenStream {
+        let mut token_buffer = TokenStream2::new();
+        token_buffer.extend(self.infallible_from_impl());
+        token_buffer.extend(self.variants.iter().enumerate().map(|it| {
+            let variant_inner_type = &it.1.path;
+            let variant_name = it.1.name(it.0);
+            let lifted_froms = it.1.lifted.iter().map(|lifted_type| {
+                let lifted_value = it.1.wrap_tokens(quote! { <#variant_inner_type>::from(value) });
+                self.variant_from_impl(&variant_name, lifted_type.to_token_stream(), lifted_value)
+            });
+            let accepted_value = it.1.wrap_tokens(quote! { value.into() });
+            let accepted_froms = it.1.config.accepts.iter().map(|accepted_type| {
+                self.variant_from_impl(&variant_name, accepted_type.to_token_stream(), accepted_value.clone())
+            });
+            let variant_from = if it.1.config.no_from {
+                TokenStream2::new()
+            } else {
+                let variant_value = it.1.wrap_tokens(quote! { value });
+                self.variant_from_impl(&variant_name, variant_inner_type.to_token_stream(), variant_value)
+            };
+            let owned_from = if it.1.config.owned {
+                let owned_value = it.1.wrap_tokens(quote! { Clone::clone(value) });
+                self.variant_from_impl(&variant_name, quote! { &#variant_inner_type }, owned_value)
+            } else {
+                TokenStream2::new()
+            };
+            let arc_from = if it.1.config.arc && !it.1.config.no_from {
+                self.variant_from_impl(
+                    &variant_name,
+                    quote! { std::sync::Arc<#variant_inner_type> },
+                    quote! { value },
+                )
+            } else {
+                TokenStream2::new()
+            };
+            quote! {
+                #variant_from
+                #arc_from
+                #owned_from
+                #( #lifted_froms )*
+                #( #accepted_froms )*
+            }
+        }));
+        token_buffer.into()
+    }
+
+    fn error_node_inherent_impl(&self) -> TokenStream {
+        let node_name = &self.node_name;
+        let generics = self.static_generics();
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        let backtrace_field = self.backtrace_field();
+        let guarded_constructors = self.variants.iter().enumerate().filter_map(|(index, variant)| {
+            let guard = variant.config.guard.as_ref()?;
+            let variant_name = variant.name(index);
+            let variant_inner_type = &variant.path;
+            let variant_value = variant.wrap_tokens(quote! { value });
+            let constructor_name = format_ident!("try_from_{}", snake_case(&variant_name.to_string()));
+            let constructor_doc = format!(
+                "Wraps `value` in the `{}` variant if it satisfies the guard of the variant, otherwise returns it back.",
+                variant_name
+            );
+            Some(quote! {
+                #[doc = #constructor_doc]
+                pub fn #constructor_name(value: #variant_inner_type) -> ::core::result::Result<Self, #variant_inner_type> {
+                    let guard: fn(&#variant_inner_type) -> bool = #guard;
+                    if guard(&value) {
+                        Ok(Self::#variant_name(#variant_value #backtrace_field))
+                    } else {
+                        Err(value)
+                    }
+                }
+            })
+        });
+        let kind_str = if self.config.kind_str {
+            let kind_str_matches = self.variants.iter().enumerate().map(|(index, variant)| {
+                let variant_name = variant.name(index);
+                quote! { Self::#variant_name(err, ..) => err.kind_str(), }
+            });
+            quote! {
+                /// Returns the machine-readable kind of the wrapped error.
+                pub fn kind_str(&self) -> &'static str {
+                    match self {
+                        #( #kind_str_matches )*
+                    }
+                }
+            }
+        } else {
+            TokenStream2::new()
+        };
+        let inner_matches = self.variants.iter().enumerate().map(|(index, variant)| {
+            let variant_name = variant.name(index);
+            let variant_error = variant.error_tokens();
+            quote! { Self::#variant_name(err, ..) => #variant_error, }
+        });
+        let log_line = if self.config.log_line {
+            let kind = if self.config.kind_str { quote! { self.kind_str() } } else { quote! { "" } };
+            let code = if self.config.repr.is_some() { quote! { self.tag() } } else { quote! { "" } };
+            quote! {
+                /// Returns a log line with the kind, the code and the message of this error, separated
+                /// by `|`.
+                pub fn to_log_line(&self) -> String {
+                    format!("{}|{}|{}", #kind, #code, self)
+                }
+            }
+        } else {
+            TokenStream2::new()
+        };
+        let detailed = self.detailed_method();
+        let context_anyhow = Self::context_anyhow_method();
+        let backtrace = self.backtrace_method();
+        let context = self.context_method();
+        quote! {
+            impl #impl_generics #node_name #ty_generics #where_clause {
+                #( #guarded_constructors )*
+                #backtrace
+                #context
+                #kind_str
+                #log_line
+                #detailed
+                #context_anyhow
+
+                /// Returns the error wrapped by the active variant.
+                pub fn inner(&self) -> &(dyn std::error::Error + 'static) {
+                    match self {
+                        #( #inner_matches )*
+                    }
+                }
+
+                /// Returns the message of each level of the error chain, starting from this node.
+                pub fn chain_messages(&self) -> Vec<String> {
+                    self.sources().map(|err| err.to_string()).collect()
+                }
+
+                /// Returns an iterator over this node and the errors in its source chain.
+                pub fn sources(&self) -> ::hierrorchy::Sources<'_> {
+                    ::hierrorchy::sources(self)
+                }
+
+                /// Returns the deepest error of the source chain, which is the root cause of this node.
+                pub fn root_cause(&self) -> &(dyn std::error::Error + 'static) {
+                    self.sources().last().expect("the source chain contains at least the node")
+                }
+            }
+        }
+        .into()
+    }
+
+    /// Generates the `detailed` method, which prints the variant of the node, its kind and its code
+    /// (when they are configured) and its message, followed by a line for each of its sources.
+    fn detailed_method(&self) -> TokenStream2 {
+        let node_name_str = self.node_name.to_string();
+        let variant_name_matches = self.variants.iter().enumerate().map(|(index, variant)| {
+            let variant_name = variant.name(index);
+            let variant_name_str = variant_name.to_string();
+            quote! { Self::#variant_name(..) => #variant_name_str, }
+        });
+        let mut tags = vec![];
+        let mut tag_args = vec![];
+        if self.config.kind_str {
+            tags.push("kind: {}");
+            tag_args.push(quote! { self.kind_str(), });
+        }
+        if self.config.repr.is_some() {
+            tags.push("code: {}");
+            tag_args.push(quote! { self.tag(), });
+        }
+        let header_format = if tags.is_empty() {
+            String::from("{}::{}: {}")
+        } else {
+            format!("{{}}::{{}} [{}]: {{}}", tags.join(", "))
+        };
+        quote! {
+            /// Returns a multi-line description of this error: the first line has the variant, the
+            /// kind and the code of the node along with its message, and each following line has the
+            /// message of an error of the source chain.
+            pub fn detailed(&self) -> String {
+                let variant = match self {
+                    #( #variant_name_matches )*
+                };
+                let mut details = format!(#header_format, #node_name_str, variant, #( #tag_args )* self);
+                for err in self.sources().skip(1) {
+                    details.push_str(&format!("\n  caused by: {}", err));
+                }
+                details
+            }
+        }
+    }
+
+    /// Generates the `context_anyhow` method, which is available only with the `anyhow` feature of
+    /// `hierrorchy`: the method is emitted through a macro of `hierrorchy`, which drops it without
+    /// the feature.
+    ///
+    /// The method is bounded on `Send + Sync`, so that it is only missing on the nodes which are
+    /// not: the bound is higher-ranked, as it would be rejected as unsatisfiable otherwise.
+    fn context_anyhow_method() -> TokenStream2 {
+        quote! {
+            ::hierrorchy::__private::with_anyhow! {
+                /// Converts this node into an `anyhow::Error`, wrapping it with the given context message.
+                pub fn context_anyhow(self, msg: &str) -> ::hierrorchy::__private::anyhow::Error
+                where
+                    for<'a> Self: Send + Sync + 'static,
+                {
+                    ::hierrorchy::__private::anyhow::Error::new(self).context(msg.to_string())
+                }
+            }
+        }
+    }
+
+    /// Generates the `to_json` method and the [`ToJson`](hierrorchy::ToJson) implementation, which
+    /// are available only with the `serde_json` feature of `hierrorchy`, through a macro of
+    /// `hierrorchy` which drops them without the feature.
+    ///
+    /// The wrapped errors implementing `ToJson` are converted recursively, which is detected with
+    /// autoref-based specialization: the method on `Wrap` takes precedence over the one on `&Wrap`.
+    fn error_node_json(&self) -> TokenStream2 {
+        let node_name = &self.node_name;
+        let node_name_str = node_name.to_string();
+        let generics = self.static_generics();
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        let source_matches = self.variants.iter().enumerate().map(|(index, variant)| {
+            let variant_name = variant.name(index);
+            let variant_name_str = variant_name.to_string();
+            let variant_error = variant.error_tokens();
+            quote! { Self::#variant_name(err, ..) => (#variant_name_str, (&Wrap(#variant_error)).json_value()), }
+        });
+        quote! {
+            ::hierrorchy::__private::with_serde_json! {
+                impl #impl_generics #node_name #ty_generics #where_clause {
+                    /// Converts this node into a JSON tree, with its type, its variant, its message and
+                    /// its source.
+                    pub fn to_json(&self) -> ::hierrorchy::__private::serde_json::Value {
+                        use ::hierrorchy::__private::serde_json::{Map, Value};
+
+                        struct Wrap<'a, T: ?Sized>(&'a T);
+
+                        trait ViaToJson {
+                            fn json_value(&self) -> Value;
+                        }
+
+                        impl<T: ::hierrorchy::ToJson<Value = Value> + ?Sized> ViaToJson for Wrap<'_, T> {
+                            fn json_value(&self) -> Value {
+                                self.0.to_json()
+                            }
+                        }
+
+                        trait ViaMessage {
+                            fn json_value(&self) -> Value;
+                        }
+
+                        impl<T: std::fmt::Display + ?Sized> ViaMessage for &Wrap<'_, T> {
+                            fn json_value(&self) -> Value {
+                                let mut object = Map::new();
+                                object.insert(String::from("message"), Value::String(self.0.to_string()));
+                                Value::Object(object)
+                            }
+                        }
+
+                        let (variant, source) = match self {
+                            #( #source_matches )*
+                        };
+                        let mut object = Map::new();
+                        object.insert(String::from("type"), Value::String(String::from(#node_name_str)));
+                        object.insert(String::from("variant"), Value::String(String::from(variant)));
+                        object.insert(String::from("message"), Value::String(self.to_string()));
+                        object.insert(String::from("source"), source);
+                        Value::Object(object)
+                    }
+                }
+
+                impl #impl_generics ::hierrorchy::ToJson for #node_name #ty_generics #where_clause {
+                    type Value = ::hierrorchy::__private::serde_json::Value;
+
+                    fn to_json(&self) -> ::hierrorchy::__private::serde_json::Value {
+                        #node_name::to_json(self)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Generates the kind enum of the node, with a fieldless variant for each variant of the node, and
+    /// the methods to get the kind of a node value.
+    fn error_node_kind(&self) -> TokenStream {
+        let Some(repr) = &self.config.repr else {
+            return TokenStream::new();
+        };
+        let node_name = &self.node_name;
+        let kind_name = Self::kind_name(node_name);
+        let visibility = &self.visibility;
+        let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+        let static_generics = self.static_generics();
+        let (static_impl_generics, static_ty_generics, static_where_clause) = static_generics.split_for_impl();
+        let from_dyn = self.kind_from_dyn_method();
+        let kind_variants = self.variants.iter().enumerate().map(|(index, variant)| {
+            let variant_name = variant.name(index);
+            match &variant.discriminant {
+                Some(discriminant) => quote! { #variant_name = #discriminant, },
+                None => quote! { #variant_name, },
+            }
+        });
+        let kind_matches = self.variants.iter().enumerate().map(|(index, variant)| {
+            let variant_name = variant.name(index);
+            quote! { Self::#variant_name(..) => #kind_name::#variant_name, }
+        });
+        let kind_doc = format!("The kind of a [`{}`], without the wrapped error.", node_name);
+        quote! {
+            #[doc = #kind_doc]
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+            #[repr(#repr)]
+            #visibility enum #kind_name {
+                #( #kind_variants )*
+            }
+
+            impl #impl_generics #node_name #ty_generics #where_clause {
+                /// Returns the kind of this node.
+                pub fn kind(&self) -> #kind_name {
+                    match self {
+                        #( #kind_matches )*
+                    }
+                }
+
+                /// Returns the numeric tag of the kind of this node.
+                pub fn tag(&self) -> #repr {
+                    self.kind() as #repr
+                }
+
+                /// Returns whether this node is of the given kind.
+                pub fn is_kind(&self, kind: #kind_name) -> bool {
+                    self.kind() == kind
+                }
+            }
+
+            impl #static_impl_generics #node_name #static_ty_generics #static_where_clause {
+                #from_dyn
+            }
+        }
+        .into()
+    }
+
+    /// Generates the `is_retryable` method and the [`Retryable`](hierrorchy::Retryable)
+    /// implementation, which forward to the wrapped error if it implements `Retryable`, and
+    /// return `false` otherwise.
+    fn retryable_impls(&self) -> TokenStream2 {
+        let node_name = &self.node_name;
+        let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+        let retryable_matches = self.variants.iter().enumerate().map(|(index, variant)| {
+            let variant_name = variant.name(index);
+            let variant_error = variant.error_tokens();
+            quote! { Self::#variant_name(err, ..) => (&Wrap(#variant_error)).is_retryable(), }
+        });
+        quote! {
+            impl #impl_generics #node_name #ty_generics #where_clause {
+                /// Returns whether the operation which failed with the wrapped error can be
+                /// retried, which is `false` if the wrapped error does not implement
+                /// `hierrorchy::Retryable`.
+                pub fn is_retryable(&self) -> bool {
+                    struct Wrap<'a, T: ?Sized>(&'a T);
+
+                    trait ViaRetryable {
+                        fn is_retryable(&self) -> bool;
+                    }
+
+                    impl<T: ::hierrorchy::Retryable + ?Sized> ViaRetryable for Wrap<'_, T> {
+                        fn is_retryable(&self) -> bool {
+                            self.0.is_retryable()
+                        }
+                    }
+
+                    trait NotRetryable {
+                        fn is_retryable(&self) -> bool;
+                    }
+
+                    impl<T: ?Sized> NotRetryable for &Wrap<'_, T> {
+                        fn is_retryable(&self) -> bool {
+                            false
+                        }
+                    }
+
+                    match self {
+                        #( #retryable_matches )*
+                    }
+                }
+            }
+
+            impl #impl_generics ::hierrorchy::Retryable for #node_name #ty_generics #where_clause {
+                fn is_retryable(&self) -> bool {
+                    #node_name::is_retryable(self)
+                }
+            }
+        }
+    }
+
+    /// Generates the `severity` and `is_at_least` methods and the
+    /// [`HasSeverity`](hierrorchy::HasSeverity) implementation of a `#[severity]` node, which
+    /// forward to the wrapped error if it implements `HasSeverity`, and use
+    /// [`Severity::Error`](hierrorchy::Severity::Error) otherwise.
+    fn severity_impls(&self) -> TokenStream2 {
+        if !self.config.severity {
+            return TokenStream2::new();
+        }
+        let node_name = &self.node_name;
+        let (impl_generics, ty_generics, where_clause) = self.generics.split_for_impl();
+        let severity_matches = self.variants.iter().enumerate().map(|(index, variant)| {
+            let variant_name = variant.name(index);
+            let variant_error = variant.error_tokens();
+            quote! { Self::#variant_name(err, ..) => (&Wrap(#variant_error)).severity(), }
+        });
+        quote! {
+            impl #impl_generics #node_name #ty_generics #where_clause {
+                /// Returns the severity of the wrapped error, which is `Severity::Error` if the
+                /// wrapped error does not implement `hierrorchy::HasSeverity`.
+                pub fn severity(&self) -> ::hierrorchy::Severity {
+                    struct Wrap<'a, T: ?Sized>(&'a T);
+
+                    trait ViaHasSeverity {
+                        fn severity(&self) -> ::hierrorchy::Severity;
+                    }
+
+                    impl<T: ::hierrorchy::HasSeverity + ?Sized> ViaHasSeverity for Wrap<'_, T> {
+                        fn severity(&self) -> ::hierrorchy::Severity {
+                            self.0.severity()
+                        }
+                    }
+
+                    trait DefaultSeverity {
+                        fn severity(&self) -> ::hierrorchy::Severity;
+                    }
+
+                    impl<T: ?Sized> DefaultSeverity for &Wrap<'_, T> {
+                        fn severity(&self) -> ::hierrorchy::Severity {
+                            ::hierrorchy::Severity::Error
+                        }
+                    }
+
+                    match self {
+                        #( #severity_matches )*
+                    }
+                }
+
+                /// Returns whether the severity of the wrapped error is at least the given one.
+                pub fn is_at_least(&self, severity: ::hierrorchy::Severity) -> bool {
+                    self.severity() >= severity
+                }
+            }
+
+            impl #impl_generics ::hierrorchy::HasSeverity for #node_name #ty_generics #where_clause {
+                fn severity(&self) -> ::hierrorchy::Severity {
+                    #node_name::severity(self)
+                }
+            }
+        }
+    }
+
+    /// Generates the `as_parts` method and the [`Coded`](hierrorchy::Coded) implementation, which
+    /// use the code of the wrapped error if it implements `Coded`, and 0 otherwise.
+    fn coded_impls(&self) -> TokenStream2 {
+        let node_name = &self.node_name;
+        let generics = self.static_generics();
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        let code_matches = self.variants.iter().enumerate().map(|(index, variant)| {
+            let variant_name = variant.name(index);
+            let variant_error = variant.error_tokens();
+            quote! { Self::#variant_name(err, ..) => (&Wrap(#variant_error)).code(), }
+        });
+        quote! {
+            impl #impl_generics #node_name #ty_generics #where_clause {
+                /// Returns the code of the wrapped error, which is 0 if the wrapped error does not
+                /// implement `hierrorchy::Coded`, and the message of this node.
+                pub fn as_parts(&self) -> (u32, String) {
+                    (::hierrorchy::Coded::code(self), self.to_string())
+                }
+            }
+
+            impl #impl_generics ::hierrorchy::Coded for #node_name #ty_generics #where_clause {
+                fn code(&self) -> u32 {
+                    struct Wrap<'a, T: ?Sized>(&'a T);
+
+                    trait ViaCoded {
+                        fn code(&self) -> u32;
+                    }
+
+                    impl<T: ::hierrorchy::Coded + ?Sized> ViaCoded for Wrap<'_, T> {
+                        fn code(&self) -> u32 {
+                            self.0.code()
+                        }
+                    }
+
+                    trait NotCoded {
+                        fn code(&self) -> u32;
+                    }
+
+                    impl<T: ?Sized> NotCoded for &Wrap<'_, T> {
+                        fn code(&self) -> u32 {
+                            0
+                        }
+                    }
+
+                    match self {
+                        #( #code_matches )*
+                    }
+                }
+            }
+        }
+    }
+
+    /// Generates the `from_dyn` method, which returns the kind of the first variant whose wrapped
+    /// type is the concrete type of the given error.
+    fn kind_from_dyn_method(&self) -> TokenStream2 {
+        let kind_name = Self::kind_name(&self.node_name);
+        let kind_checks = self.variants.iter().enumerate().map(|(index, variant)| {
+            let variant_name = variant.name(index);
+            let variant_inner_type = &variant.path;
+            quote! {
+                if err.is::<#variant_inner_type>() {
+                    return Some(#kind_name::#variant_name);
+                }
+            }
+        });
+        quote! {
+            /// Returns the kind of the variant wrapping the concrete type of `err`, trying the
+            /// variants in declaration order, or `None` if no variant wraps it.
+            pub fn from_dyn(err: &(dyn std::error::Error + 'static)) -> Option<#kind_name> {
+                #( #kind_checks )*
+                None
+            }
+        }
+    }
+
+    /// Generates an `#[erased]` node: a struct with the kind of the wrapped error, among the kinds
+    /// of a fieldless enum, and the wrapped error in a `Box`.
+    fn erased_error_node(&self) -> TokenStream {
+        let node_name = &self.node_name;
+        let kind_name = Self::kind_name(node_name);
+        let visibility = &self.visibility;
+        let passthrough = &self.config.passthrough;
+        let derive_debug = if self.config.no_debug { TokenStream2::new() } else { quote! { #[derive(Debug)] } };
+        let kind_variants = self.variants.iter().enumerate().map(|(index, variant)| {
+            let variant_name = variant.name(index);
+            let variant_doc = format!(
+                "The kind of a wrapped `{}`.",
+                variant.type_name()
+            );
+            quote! {
+                #[doc = #variant_doc]
+                #variant_name,
+            }
+        });
+        let kind_doc = format!("The kind of the error wrapped by a [`{}`].", node_name);
+        let (source_type, new_source, source_ref, source_into_inner, root_cause) = if self.config.cache_root_cause {
+            (
+                quote! { ::hierrorchy::CachedSource },
+                quote! { ::hierrorchy::CachedSource::new(Box::new(value)) },
+                quote! { self.source.get() },
+                quote! { self.source.into_inner() },
+                quote! { self.source.root_cause() },
+            )
+        } else {
+            (
+                quote! { Box<dyn std::error::Error + Send + Sync> },
+                quote! { Box::new(value) },
+                quote! { &*self.source },
+                quote! { self.source },
+                quote! { self.sources().last().expect("the source chain contains at least the node") },
+            )
+        };
+        let froms = self.variants.iter().enumerate().map(|(index, variant)| {
+            let variant_name = variant.name(index);
+            let variant_inner_type = &variant.path;
+            quote! {
+                impl From<#variant_inner_type> for #node_name {
+                    fn from(value: #variant_inner_type) -> Self {
+                        #node_name {
+                            kind: #kind_name::#variant_name,
+                            source: #new_source,
+                        }
+                    }
+                }
+            }
+        });
+        let infallible_from = self.infallible_from_impl();
+        let from_dyn = self.kind_from_dyn_method();
+        let mut token_buffer: TokenStream = quote! {
+            #( #passthrough )*
+            #derive_debug
+            #visibility struct #node_name {
+                kind: #kind_name,
+                source: #source_type,
+            }
+
+            #[doc = #kind_doc]
+            #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+            #visibility enum #kind_name {
+                #( #kind_variants )*
+            }
+
+            impl std::error::Error for #node_name {
+                fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                    Some(#source_ref)
+                }
+            }
+
+            #( #froms )*
+            #infallible_from
+
+            impl #node_name {
+                /// Returns the kind of the wrapped error.
+                pub fn kind(&self) -> #kind_name {
+                    self.kind
+                }
+
+                /// Returns whether the wrapped error is of the given kind.
+                pub fn is_kind(&self, kind: #kind_name) -> bool {
+                    self.kind == kind
+                }
+
+                #from_dyn
+
+                /// Returns the wrapped error.
+                pub fn inner(&self) -> &(dyn std::error::Error + Send + Sync + 'static) {
+                    #source_ref
+                }
+
+                /// Consumes this node, returning the wrapped error.
+                pub fn into_inner(self) -> Box<dyn std::error::Error + Send + Sync> {
+                    #source_into_inner
+                }
+
+                /// Returns the message of each level of the error chain, starting from this node.
+                pub fn chain_messages(&self) -> Vec<String> {
+                    self.sources().map(|err| err.to_string()).collect()
+                }
+
+                /// Returns an iterator over this node and the errors in its source chain.
+                pub fn sources(&self) -> ::hierrorchy::Sources<'_> {
+                    ::hierrorchy::sources(self)
+                }
+
+                /// Returns the deepest error of the source chain, which is the root cause of this node.
+                pub fn root_cause(&self) -> &(dyn std::error::Error + 'static) {
+                    #root_cause
+                }
+            }
+        }
+        .into();
+        token_buffer.extend(TokenStream::from(self.variant_types_const()));
+        token_buffer.extend(self.error_node_display_impl());
+        if self.config.no_debug {
+            token_buffer.extend(TokenStream::from(manual_debug_assertion(&self.node_name, "#[no_debug]")));
+        }
+        token_buffer
+    }
+
+    fn kind_name(node_name: &Ident) -> Ident {
+        format_ident!("{}Kind", node_name)
+    }
+
+    /// Returns the generics of the node, with an additional `'static` bound on each type parameter,
+    /// as required to return the variants as `&(dyn Error + 'static)`.
+    fn static_generics(&self) -> Generics {
+        static_generics(&self.generics)
+    }
+
+}
+
+
@@ -0,0 +1,143 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{Attribute, Data, DeriveInput, Error as SynError, Fields, Ident, LitStr, Member, Type};
+
+use crate::error_node::{default_node_separator, static_generics};
+
+/// An existing enum deriving `ErrorNode`, whose variants wrap the errors of the node.
+pub struct ErrorNodeDerive {
+    input: DeriveInput,
+    /// The prefix printed before the source, given with `#[error_node(prefix = "...")]`.
+    prefix: Option<LitStr>,
+    variants: Vec<DerivedVariant>,
+}
+
+/// A variant of a derived node, with the field holding its source.
+struct DerivedVariant {
+    ident: Ident,
+    source_member: Member,
+    source_type: Type,
+    /// Whether to generate the `From` implementation for the source, which is only done for
+    /// variants with a single field, as the other fields could not be set.
+    has_from: bool,
+}
+
+impl ErrorNodeDerive {
+    pub fn new(input: DeriveInput) -> syn::Result<Self> {
+        let prefix = Self::parse_prefix(&input.attrs)?;
+        let Data::Enum(data) = &input.data else {
+            return Err(SynError::new_spanned(&input.ident, "ErrorNode can only be derived on enums"));
+        };
+        let variants = data
+            .variants
+            .iter()
+            .map(|variant| {
+                let fields: Vec<_> = match &variant.fields {
+                    Fields::Named(fields) => fields.named.iter().collect(),
+                    Fields::Unnamed(fields) => fields.unnamed.iter().collect(),
+                    Fields::Unit => vec![],
+                };
+                let members: Vec<Member> = variant.fields.members().collect();
+                let source_index = if fields.len() == 1 {
+                    0
+                } else {
+                    let marked: Vec<usize> = fields
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, field)| field.attrs.iter().any(|it| it.path().is_ident("source")))
+                        .map(|(index, _)| index)
+                        .collect();
+                    match marked.as_slice() {
+                        [index] => *index,
+                        _ => {
+                            return Err(SynError::new_spanned(
+                                &variant.ident,
+                                "a variant of an error node must have a single field, or mark the field \
+                                 wrapping its source with #[source]",
+                            ))
+                        }
+                    }
+                };
+                Ok(DerivedVariant {
+                    ident: variant.ident.clone(),
+                    source_member: members[source_index].clone(),
+                    source_type: fields[source_index].ty.clone(),
+                    has_from: fields.len() == 1,
+                })
+            })
+            .collect::<syn::Result<_>>()?;
+        Ok(ErrorNodeDerive {
+            input,
+            prefix,
+            variants,
+        })
+    }
+
+    fn parse_prefix(attributes: &[Attribute]) -> syn::Result<Option<LitStr>> {
+        let mut prefix = None;
+        for attribute in attributes.iter().filter(|it| it.path().is_ident("error_node")) {
+            attribute.parse_nested_meta(|meta| {
+                if meta.path.is_ident("prefix") {
+                    prefix = Some(meta.value()?.parse()?);
+                    Ok(())
+                } else {
+                    Err(meta.error("unknown error_node option, expected: prefix"))
+                }
+            })?;
+        }
+        Ok(prefix)
+    }
+
+    pub fn to_token_stream(&self) -> TokenStream {
+        let node_name = &self.input.ident;
+        let generics = static_generics(&self.input.generics);
+        let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+        let prefix = self.prefix.as_ref().map_or_else(|| node_name.to_string(), LitStr::value);
+        let separator = default_node_separator();
+        let display_matches = self.variants.iter().map(|variant| {
+            let variant_name = &variant.ident;
+            let source_member = &variant.source_member;
+            quote! {
+                Self::#variant_name { #source_member: err, .. } => write!(f, "{}{}{}", #prefix, #separator, err),
+            }
+        });
+        let source_matches = self.variants.iter().map(|variant| {
+            let variant_name = &variant.ident;
+            let source_member = &variant.source_member;
+            quote! { Self::#variant_name { #source_member: err, .. } => Some(err), }
+        });
+        let from_impls = self.variants.iter().filter(|variant| variant.has_from).map(|variant| {
+            let variant_name = &variant.ident;
+            let source_member = &variant.source_member;
+            let source_type = &variant.source_type;
+            let (impl_generics, ty_generics, where_clause) = self.input.generics.split_for_impl();
+            quote! {
+                impl #impl_generics From<#source_type> for #node_name #ty_generics #where_clause {
+                    fn from(value: #source_type) -> Self {
+                        Self::#variant_name { #source_member: value }
+                    }
+                }
+            }
+        });
+        quote! {
+            impl #impl_generics std::fmt::Display for #node_name #ty_generics #where_clause {
+                fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                    match self {
+                        #( #display_matches )*
+                    }
+                }
+            }
+
+            impl #impl_generics std::error::Error for #node_name #ty_generics #where_clause {
+                fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                    match self {
+                        #( #source_matches )*
+                    }
+                }
+            }
+
+            #( #from_impls )*
+        }
+        .into()
+    }
+}
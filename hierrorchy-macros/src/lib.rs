@@ -0,0 +1,1407 @@
+//! Procedural macros of the [hierrorchy](https://crates.io/crates/hierrorchy) crate.
+//!
+//! This crate is not meant to be used directly: the macros are re-exported by `hierrorchy`,
+//! together with the helpers used by the generated code.
+#![deny(missing_docs)]
+mod error_bridge;
+mod error_hierarchy;
+mod error_leaf;
+mod error_node;
+mod error_node_derive;
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput, Error as SynError, Item};
+
+use crate::{
+    error_bridge::ErrorBridge,
+    error_hierarchy::ErrorHierarchy,
+    error_leaf::{ErrorLeaf, ErrorLeafConfig, ErrorLeafEnum},
+    error_node::{ErrorNode, ErrorNodes},
+    error_node_derive::ErrorNodeDerive,
+};
+
+/// Attribute to mark a Struct (or an Enum, see below) definition as an error leaf.
+/// Implementation of `Display` and `Error` is created by the macro.
+///
+/// The struct definition is emitted unchanged, so any other attribute on it (e.g. `#[cfg_attr]`
+/// gated derives) is preserved.
+///
+/// # Examples
+/// The message can be written as a format macro call.
+///
+/// The scope of the format macro is inside the struct, so internal fields can be accessed with
+/// `self`. The message is evaluated synchronously inside [std::fmt::Display::fmt], where `self` is
+/// a shared reference: fields can be borrowed, but not moved out of the struct.
+/// ```
+/// use hierrorchy::error_leaf;
+///
+/// // Format macro form
+/// #[error_leaf(message = format!("{} is wrong", self.myfield))]
+/// struct MyError {
+///    myfield: String,
+/// }
+/// ```
+///
+/// The format string can refer to `SELF_TYPE`, which is the name of the struct, so that the message
+/// follows the renames of the struct.
+/// ```
+/// use hierrorchy::error_leaf;
+///
+/// #[error_leaf(message = format!("{SELF_TYPE}: broken"))]
+/// struct BrokenPipeError {}
+///
+/// assert_eq!(BrokenPipeError {}.to_string(), "BrokenPipeError: broken");
+/// ```
+///
+/// Otherwise, the message must be a constant `&str` expression, like a literal, a `const` item or
+/// a `concat!` call. It is evaluated at compile time and written without any formatting nor
+/// allocation.
+/// ```
+/// use hierrorchy::error_leaf;
+///
+/// const PREFIX: &str = "storage";
+///
+/// // Constant form
+/// #[error_leaf(message = PREFIX)]
+/// struct StorageError {}
+///
+/// #[error_leaf(message = concat!("storage", ": disk is full"))]
+/// struct DiskFullError {}
+///
+/// assert_eq!(DiskFullError {}.to_string(), "storage: disk is full");
+/// ```
+///
+/// The message can also be written as a string literal before the keywords, where placeholders
+/// like `{myfield}` refer to the named fields of the struct directly, without `self`. The
+/// placeholders are checked when the macro is expanded, so a placeholder which is not a field is
+/// reported on the message.
+/// ```
+/// use hierrorchy::error_leaf;
+///
+/// // Interpolated form
+/// #[error_leaf("value {value} is invalid, expected at most {max:?}")]
+/// struct InvalidValueError {
+///    value: u32,
+///    max: u32,
+/// }
+///
+/// assert_eq!(InvalidValueError { value: 7, max: 5 }.to_string(), "value 7 is invalid, expected at most 5");
+/// ```
+///
+/// ```compile_fail
+/// use hierrorchy::error_leaf;
+///
+/// #[error_leaf("value {valeu} is invalid")]
+/// struct InvalidValueError {
+///    value: u32,
+/// }
+/// ```
+///
+/// # Arguments
+/// This attribute macro can be configured with keywords, with the structure `<keyword> = <value>`,
+/// separated by commas. Trailing commas are accepted.
+///
+/// The available keywords are reported in the table below.
+///
+/// | keyword | Required? | Description |
+/// | --- | --- | --- |
+/// | `message` | Y (unless given as a string before the keywords, or `self_message`, `transparent`, `plural` or `fmt` is set) | The message format (or constant message) to use in the [std::fmt::Display] implementation. |
+/// | `derive_debug` | N | Whether to add the derive macro for [std::fmt::Debug] trait. Defaults to `true`. |
+/// | `no_debug` | N | Whether to skip the derive macro for [std::fmt::Debug], checking that the type implements it manually (see below). Cannot be used with `derive_debug = true`. Defaults to `false`. |
+/// | `typed_builder` | N | Whether to generate a builder for the struct (see below). Defaults to `false`. |
+/// | `hashable` | N | Whether to add the derive macros for [PartialEq], [Eq] and [std::hash::Hash]. Defaults to `false`. |
+/// | `expose_kind` | N | The name of a field to return by reference from a generated `kind()` method. |
+/// | `kind_str` | N | A machine-readable kind, exposed as the `KIND` associated constant and by the `kind_str()` method. |
+/// | `precompute` | N | Whether to compute the message once, when the error is created (see below). Defaults to `false`. |
+/// | `severity` | N | The [severity](https://docs.rs/hierrorchy/latest/hierrorchy/enum.Severity.html) of the error (`Warn`, `Error` or `Fatal`), returned by the generated `severity()` method; `is_at_least(severity)` is generated as well. Without it, the severity is `Error`. |
+/// | `log_line` | N | Whether to generate a `to_log_line()` method, returning `<kind>|<code>|<message>`, where the kind is the one given with `kind_str` and the code is the one given with `code` (or empty). Defaults to `false`. |
+/// | `self_message` | N | Whether the only field of the struct is the message of the error (see below). Defaults to `false`. |
+/// | `source_fn` | N | An expression returning `Option<&(dyn Error + 'static)>`, like a method call on `self`, used as the [std::error::Error::source] of the leaf (see below). By default, a leaf has no source. |
+/// | `source` | N | The name (or the index, for a tuple struct) of the field holding the source of the leaf, which can also be marked with a `#[source]` attribute instead (see below). |
+/// | `into_io` | N | The name of a [std::io::ErrorKind] variant (e.g. `Other`): generates a [std::convert::From] implementation converting the leaf into an [std::io::Error] of that kind, with the message of the leaf. |
+/// | `timestamp` | N | Whether to record the time at which the error is created, returned by the generated `created_at()` method (see below). Defaults to `false`. |
+/// | `clone_message_only` | N | Whether to implement [Clone] by cloning the precomputed message only, setting the fields to their default value (see below). Requires `precompute`. Defaults to `false`. |
+/// | `plural` | N | Written as `plural(<count>, "<singular>", "<plural>")`: the message is the singular format when `count` equals 1, and the plural format otherwise, with the count in place of `{}` (see below). Replaces `message`. |
+/// | `fmt` | N | A closure `\|this, f\| { ... }` used as the body of [std::fmt::Display::fmt], taking the leaf and the formatter, for messages too complex for a format string (see below). Replaces `message`. |
+/// | `retryable` | N | Whether the operation failing with this error can be retried: generates an `is_retryable()` method returning `true`, and implements [`hierrorchy::Retryable`](https://docs.rs/hierrorchy/latest/hierrorchy/trait.Retryable.html), so that the nodes wrapping the leaf report it as retryable. Defaults to `false`. |
+/// | `code` | N | A numeric code (a `u32`) of the error, exposed as the `CODE` associated constant and by the `code()` method, and implementing [`hierrorchy::Coded`](https://docs.rs/hierrorchy/latest/hierrorchy/trait.Coded.html). The `as_parts()` method, generated on every leaf, returns the code (0 without `code`) and the message. |
+/// | `transparent` | N | Whether the only field of the struct is an error which the leaf wraps: the message of the leaf is the one of the field, which is also its [std::error::Error::source] (see below). Defaults to `false`. |
+/// | `clone_via_string` | N | Whether to implement [Clone], replacing boxed errors with their message (see below). Defaults to `false`. |
+///
+/// Flag keywords (like `typed_builder`) can also be written alone, meaning `<keyword> = true`.
+///
+/// Unknown keywords are rejected, and the error lists the available ones:
+/// ```compile_fail
+/// use hierrorchy::error_leaf;
+///
+/// #[error_leaf(mesage = format!("invalid port"))]
+/// struct InvalidPortError {}
+/// ```
+///
+/// ## Builder
+/// When `typed_builder` is set, a `<Name>Builder` struct is generated with a setter for each field
+/// and a `build()` method, accessible through `<Name>::builder()`.
+/// Fields with an `Option` type are optional and default to `None`; every other field is required
+/// and `build()` returns a `<Name>BuilderError` if it has not been set.
+/// ```
+/// use hierrorchy::error_leaf;
+///
+/// #[error_leaf(message = format!("request {} failed", self.id), typed_builder)]
+/// struct RequestError {
+///     id: u32,
+///     reason: Option<String>,
+/// }
+///
+/// let error = RequestError::builder().id(42).build().unwrap();
+/// assert_eq!(error.reason, None);
+/// ```
+///
+/// ## Precomputed messages
+/// When `precompute` is set, the message is formatted once by the generated `new` constructor,
+/// and stored in a hidden `precomputed_message` field, which is then written by
+/// [std::fmt::Display]. The constructor takes a value for each field of the struct, in
+/// declaration order, and must be used to create the error, as the struct has a field added by
+/// the macro.
+/// ```
+/// use hierrorchy::error_leaf;
+///
+/// #[error_leaf(message = format!("{} is wrong", self.myfield), precompute)]
+/// struct MyError {
+///    myfield: String,
+/// }
+///
+/// let error = MyError::new(String::from("value"));
+/// assert_eq!(error.to_string(), "value is wrong");
+/// ```
+///
+/// With `clone_message_only` as well, [Clone] is implemented even if the fields are not `Clone`:
+/// the clone has the same message, but each of its fields is set to its [Default] value, so the
+/// data of the original error is lost in the clone. Every field must implement [Default].
+/// ```
+/// use hierrorchy::error_leaf;
+///
+/// #[derive(Debug)]
+/// struct Connection {
+///     host: String,
+/// }
+///
+/// #[error_leaf(message = format!("connection to {} lost", self.connection.as_ref().map_or("?", |it| &it.host)), precompute, clone_message_only)]
+/// struct ConnectionLostError {
+///     connection: Option<Connection>,
+/// }
+///
+/// let error = ConnectionLostError::new(Some(Connection { host: String::from("db") }));
+/// let cloned = error.clone();
+/// assert_eq!(cloned.to_string(), "connection to db lost");
+/// assert!(cloned.connection.is_none());
+/// ```
+///
+/// ## Timestamps
+/// When `timestamp` is set, the time at which the error is created is stored in a hidden
+/// `created_at` field, as a [std::time::SystemTime], and returned by the generated `created_at()`
+/// method. As with `precompute`, the error must be created with the generated `new` constructor.
+/// ```
+/// use hierrorchy::error_leaf;
+///
+/// #[error_leaf(message = format!("login of {} failed", self.user), timestamp)]
+/// struct LoginError {
+///    user: String,
+/// }
+///
+/// let error = LoginError::new(String::from("admin"));
+/// assert!(error.created_at() <= std::time::SystemTime::now());
+/// ```
+///
+/// ## Non-exhaustive leaves
+/// A `#[non_exhaustive]` struct cannot be created with a struct expression outside of its crate,
+/// so the same `new` constructor is generated for it, taking a value for each field in declaration
+/// order. The attribute is kept on the struct.
+///
+/// ## `thiserror` compatibility
+/// With the `thiserror_compat` feature, the message of a struct can be given with a
+/// `thiserror`-like `#[error("...")]` attribute instead of the `message` keyword, to ease the
+/// migration from `thiserror`. The arguments of the attribute are the ones of a format macro, and
+/// the format string can refer to the named fields of the struct directly.
+/// ```ignore
+/// use hierrorchy::error_leaf;
+///
+/// #[error_leaf]
+/// #[error("{myfield} is wrong")]
+/// struct MyError {
+///    myfield: String,
+/// }
+/// ```
+///
+/// ## Message-carrying leaves
+/// When `self_message` is set, the struct must have exactly one field, of type [String], which is
+/// the message of the error: the `message` keyword is not needed. `From<String>`, `From<&str>` and
+/// an infallible [std::str::FromStr] are implemented as well.
+/// ```
+/// use hierrorchy::error_leaf;
+///
+/// #[error_leaf(self_message)]
+/// struct ProtocolError {
+///     message: String,
+/// }
+///
+/// let error: ProtocolError = "unexpected frame".parse().unwrap();
+/// assert_eq!(error.to_string(), "unexpected frame");
+/// ```
+///
+/// ## Transparent leaves
+/// When `transparent` is set, the struct must have exactly one field, whose type implements
+/// [std::error::Error]: the leaf is printed as the wrapped error, which is returned as its source.
+/// This allows to put a foreign error in a hierarchy without changing its message.
+/// ```
+/// use hierrorchy::error_leaf;
+/// use std::error::Error;
+///
+/// #[error_leaf(transparent)]
+/// struct AddrParseError(std::net::AddrParseError);
+///
+/// let error = AddrParseError("localhost".parse::<std::net::IpAddr>().unwrap_err());
+/// assert_eq!(error.to_string(), "invalid IP address syntax");
+/// assert!(error.source().unwrap().is::<std::net::AddrParseError>());
+/// ```
+///
+/// ## Pluralized messages
+/// With `plural(<count>, "<singular>", "<plural>")`, the message depends on a count, like the
+/// number of items which failed: the singular format string is used when the count equals 1, and
+/// the plural one otherwise. The count is interpolated in place of `{}`, and can be omitted from
+/// either format string.
+/// ```
+/// use hierrorchy::error_leaf;
+///
+/// #[error_leaf(plural(self.count, "{} file failed", "{} files failed"))]
+/// struct UploadError {
+///     count: usize,
+/// }
+///
+/// assert_eq!(UploadError { count: 1 }.to_string(), "1 file failed");
+/// assert_eq!(UploadError { count: 3 }.to_string(), "3 files failed");
+/// ```
+///
+/// ## Custom formatting
+/// With `fmt = |this, f| { ... }`, the closure is the whole body of the [std::fmt::Display::fmt]
+/// implementation, taking the leaf as `&Self` and the formatter: it can write the message with
+/// several `write!` calls, conditionally. The closure must not capture any variable.
+/// ```
+/// use hierrorchy::error_leaf;
+/// use std::fmt::Write;
+///
+/// #[error_leaf(fmt = |this, f| {
+///     write!(f, "missing fields:")?;
+///     for field in &this.fields {
+///         write!(f, " {}", field)?;
+///     }
+///     f.write_char('.')
+/// })]
+/// struct MissingFieldsError {
+///     fields: Vec<&'static str>,
+/// }
+///
+/// let error = MissingFieldsError { fields: vec!["name", "port"] };
+/// assert_eq!(error.to_string(), "missing fields: name port.");
+/// ```
+///
+/// ## Sources
+/// A leaf has no source by default. When the leaf wraps its cause in a field, the field can be
+/// marked with `#[source]`, or given with `source = <field>`, to be returned by
+/// [std::error::Error::source]:
+/// ```
+/// use hierrorchy::error_leaf;
+/// use std::error::Error;
+///
+/// #[error_leaf(message = format!("invalid port {}", self.port))]
+/// struct InvalidPortError {
+///     port: String,
+///     #[source]
+///     cause: std::num::ParseIntError,
+/// }
+///
+/// #[error_leaf(message = format!("invalid timeout"), source = 0)]
+/// struct InvalidTimeoutError(std::num::ParseIntError);
+///
+/// let cause = "80a".parse::<u16>().unwrap_err();
+/// let error = InvalidPortError { port: String::from("80a"), cause: cause.clone() };
+/// assert!(error.source().unwrap().is::<std::num::ParseIntError>());
+/// assert!(InvalidTimeoutError(cause).source().is_some());
+/// ```
+///
+/// With `source_fn`, the [std::error::Error::source] method
+/// returns the value of the given expression, where `self` is the leaf, so the source can be
+/// computed from several fields or lazily.
+/// ```
+/// use hierrorchy::error_leaf;
+/// use std::error::Error;
+///
+/// #[error_leaf(message = format!("download of {} failed", self.url), source_fn = self.last_error())]
+/// struct DownloadError {
+///     url: String,
+///     attempts: Vec<std::io::Error>,
+/// }
+///
+/// impl DownloadError {
+///     fn last_error(&self) -> Option<&(dyn Error + 'static)> {
+///         self.attempts.last().map(|it| it as &(dyn Error + 'static))
+///     }
+/// }
+///
+/// let error = DownloadError {
+///     url: String::from("https://example.com"),
+///     attempts: vec![std::io::Error::other("timed out")],
+/// };
+/// assert_eq!(error.source().unwrap().to_string(), "timed out");
+/// ```
+///
+/// [std::error::Error::provide] is unstable, so by default the typed context of the source (like
+/// its backtrace) cannot be requested through the leaf with `std::error::request_ref`. On a nightly
+/// toolchain, building with `RUSTFLAGS="--cfg hierrorchy_provide"` makes the leaves with a source
+/// implement `provide` by forwarding the requests to their source; every crate declaring such
+/// leaves must then enable `#![feature(error_generic_member_access)]`.
+///
+/// ## Cloning boxed errors
+/// A leaf holding a `Box<dyn Error>` cannot derive [Clone]. When `clone_via_string` is set, [Clone]
+/// is implemented by replacing each `Box<dyn Error>` field (possibly with other bounds, like
+/// `Send + Sync`) with a [`MessageError`](https://docs.rs/hierrorchy/latest/hierrorchy/struct.MessageError.html)
+/// holding the message of the boxed error. The clone keeps the messages, but loses the concrete
+/// type of the boxed errors. The other fields are cloned.
+/// ```
+/// use hierrorchy::error_leaf;
+/// use std::error::Error;
+///
+/// #[error_leaf(message = format!("sync of {} failed: {}", self.path, self.cause), clone_via_string)]
+/// struct SyncError {
+///     path: String,
+///     cause: Box<dyn Error + Send + Sync>,
+/// }
+///
+/// let error = SyncError { path: String::from("a.txt"), cause: "disk unplugged".into() };
+/// assert_eq!(error.clone().to_string(), error.to_string());
+/// ```
+///
+/// ## Enum leaves
+/// An enum can be an error leaf as well, when the error has a closed set of cases which are not
+/// other errors. Each variant requires a `#[msg(...)]` attribute, with the arguments of a format
+/// macro: the fields of named variants can be used by name, and the fields of tuple variants as
+/// `_0`, `_1`, and so on. The `message`, `typed_builder`, `expose_kind`, `precompute`,
+/// `clone_via_string` and `self_message` keywords are not supported on enums.
+/// ```
+/// use hierrorchy::error_leaf;
+///
+/// #[error_leaf]
+/// enum InputError {
+///     #[msg("the input is empty")]
+///     Empty,
+///     #[msg("the input is too long ({_0} characters)")]
+///     TooLong(usize),
+///     #[msg("invalid character '{character}' at {position}")]
+///     InvalidCharacter { character: char, position: usize },
+/// }
+///
+/// assert_eq!(InputError::TooLong(12).to_string(), "the input is too long (12 characters)");
+/// ```
+///
+/// ## Hashable leaves
+/// When `hashable` is set, every field of the struct must implement [std::hash::Hash] and [Eq],
+/// otherwise the compilation fails:
+/// ```compile_fail
+/// use hierrorchy::error_leaf;
+///
+/// #[error_leaf(message = format!("invalid ratio"), hashable)]
+/// struct RatioError {
+///     ratio: f64,
+/// }
+/// ```
+///
+/// ## Manual Debug
+/// With `no_debug`, [std::fmt::Debug] is not derived, e.g. to redact the fields of the leaf or
+/// when the struct already derives it. As [std::error::Error] requires [std::fmt::Debug], the
+/// leaf is checked to implement it, with an error explaining why when it does not.
+/// ```
+/// use hierrorchy::error_leaf;
+///
+/// #[error_leaf("invalid password for {user}", no_debug)]
+/// pub struct InvalidPasswordError {
+///     user: String,
+///     password: String,
+/// }
+///
+/// impl std::fmt::Debug for InvalidPasswordError {
+///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+///         f.debug_struct("InvalidPasswordError")
+///             .field("user", &self.user)
+///             .field("password", &"<redacted>")
+///             .finish()
+///     }
+/// }
+/// ```
+/// ```compile_fail
+/// use hierrorchy::error_leaf;
+///
+/// #[error_leaf("invalid password", no_debug)]
+/// pub struct InvalidPasswordError {}
+/// ```
+#[proc_macro_attribute]
+pub fn error_leaf(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let config = parse_macro_input!(attr as ErrorLeafConfig);
+    match parse_macro_input!(item as Item) {
+        Item::Struct(struct_def) => match ErrorLeaf::new(config, struct_def) {
+            Ok(leaf) => leaf.to_token_stream(),
+            Err(e) => e.to_compile_error().into(),
+        },
+        Item::Enum(enum_def) => ErrorLeafEnum::new(config, enum_def).to_token_stream(),
+        other => SynError::new_spanned(other, "error_leaf can only be used on structs and enums")
+            .to_compile_error()
+            .into(),
+    }
+}
+
+/// Function-like proc macro to construct error nodes.
+/// The body requires the following format:
+/// `type (name)[<generics>]<variants> [where (predicates)] [= (string)]`
+/// where `name` is the name to give to the error node (an enum), `variants` is a comma-separated list of other
+/// errors (both leaves and nodes), and `string` is an optional string to use rather than the node
+/// name when printing the error node.
+///
+/// The declaration can start with a visibility, like `pub`, `pub(crate)` or `pub(in path)`, which
+/// is given to the enum and to the other generated types, like the kind enum.
+///
+/// The prefix can also be given as an expression implementing [std::fmt::Display] (e.g. a function
+/// call), which is evaluated each time the node is printed. The value is written directly into the
+/// formatter, so a translation function returning a `Cow<'static, str>` allocates only for the
+/// prefixes it builds at runtime.
+///
+/// A literal prefix cannot contain format placeholders, as it is printed as-is: braces must be
+/// escaped as `{{` and `}}`. A prefix with placeholders must be given as an expression instead,
+/// e.g. with `format!(...)`.
+///
+/// The generated code refers to the standard library with fully qualified paths, so the macro
+/// can be used in any module without importing [std::error::Error].
+///
+/// # Examples:
+/// ```
+/// use hierrorchy::{error_leaf, error_node};
+///
+/// #[error_leaf(message = format!("error child 1"))]
+/// pub struct ErrorChild1 {}
+///
+/// error_node! { type MyErrorNode<ErrorChild1> = "custom prefix" }
+/// ```
+///
+/// ```compile_fail
+/// use hierrorchy::{error_leaf, error_node};
+///
+/// #[error_leaf(message = format!("error child 1"))]
+/// pub struct ErrorChild1 {}
+///
+/// error_node! { type MyErrorNode<ErrorChild1> = "oops {}" }
+/// ```
+///
+/// ## Brace form
+/// The node can also be declared with its variants in braces, which avoids the resemblance with
+/// generic parameters: `(name)[<generics>] [= (string)] { variants }`.
+/// ```
+/// use hierrorchy::{error_leaf, error_node};
+///
+/// #[error_leaf(message = format!("error child 1"))]
+/// pub struct ErrorChild1 {}
+///
+/// error_node! { MyErrorNode = "custom prefix" { ErrorChild1 } }
+/// ```
+///
+/// ## Variants with paths
+/// > Since version 0.2.0
+///
+/// error_node also accept variants in the form of paths, e.g. `std::io::Error`, including paths
+/// starting with `::`, e.g. `::std::io::Error`.
+///
+/// This allows to write:
+/// ```ignore
+/// error_node! { type MyErrorNode<std::io::Error> = "custom message" }
+/// ```
+/// rather than:
+/// ```ignore
+/// use std::io::Error as IoError;
+/// error_node! { type MyErrorNode<IoError> = "custom message" }
+/// ```
+///
+/// ## Named variants
+/// The variants of the node are named `Variant0`, `Variant1`, ... in declaration order. A variant
+/// can be given a name with `<name> = <error>`, which is used in the enum, in the kind enum and
+/// in the messages of the generated methods. Named and unnamed variants can be mixed, but the
+/// names must be unique.
+/// ```
+/// use hierrorchy::{error_leaf, error_node};
+/// use std::error::Error;
+///
+/// #[error_leaf(message = format!("invalid syntax"))]
+/// pub struct ParseError {}
+///
+/// error_node! { type ConfigErrorNode<Io = std::io::Error, Parse = ParseError> = "config" }
+///
+/// let error: ConfigErrorNode = ParseError {}.into();
+/// assert!(matches!(error, ConfigErrorNode::Parse(_)));
+/// ```
+///
+/// ```compile_fail
+/// use hierrorchy::error_node;
+/// use std::error::Error;
+///
+/// error_node! { type ConfigErrorNode<Io = std::io::Error, Io = std::fmt::Error> = "config" }
+/// ```
+///
+/// ## Variant messages
+/// A variant followed by `: "<message>"` is printed with `message` instead of the prefix of the
+/// node, where `{}` is replaced with the wrapped error, and can be omitted. This is equivalent to
+/// a `#[display]` attribute writing the message, so the two cannot be combined.
+/// ```
+/// use hierrorchy::{error_leaf, error_node};
+/// use std::error::Error;
+///
+/// #[error_leaf(message = format!("invalid syntax"))]
+/// pub struct ParseError {}
+///
+/// error_node! { type ConfigErrorNode<Io = std::io::Error: "I/O failed: {}", ParseError> = "config" }
+///
+/// let error: ConfigErrorNode = std::io::Error::other("broken pipe").into();
+/// assert_eq!(error.to_string(), "I/O failed: broken pipe");
+/// let error: ConfigErrorNode = ParseError {}.into();
+/// assert_eq!(error.to_string(), "config: invalid syntax");
+/// ```
+///
+/// ## Trait object variants
+/// A variant can wrap a boxed trait object, like `Box<dyn MyDomainError>`, as long as the trait
+/// has [std::error::Error] as a supertrait: the boxed error is returned by `source()` through trait
+/// upcasting. A trait without the bound is rejected at compile time.
+/// ```
+/// use hierrorchy::error_node;
+/// use std::error::Error;
+///
+/// pub trait DomainError: Error {}
+///
+/// error_node! { type DomainErrorNode<Box<dyn DomainError>> = "domain" }
+/// ```
+///
+/// ```compile_fail
+/// use hierrorchy::error_node;
+///
+/// pub trait NotAnError {}
+///
+/// error_node! { type DomainErrorNode<Box<dyn NotAnError>> = "domain" }
+/// ```
+///
+/// ## Catch-all variant
+/// A last variant written as `..` is named `Other` and wraps a
+/// `Box<dyn std::error::Error + Send + Sync>`, which the node can be converted from: this allows a
+/// node at an application boundary to absorb unforeseen errors, while keeping its typed variants.
+/// ```
+/// use hierrorchy::{error_leaf, error_node};
+/// use std::error::Error;
+///
+/// #[error_leaf(message = format!("invalid syntax"))]
+/// pub struct ParseError {}
+///
+/// error_node! { type AppErrorNode<ParseError, ..> = "app" }
+///
+/// let error: AppErrorNode = Box::<dyn Error + Send + Sync>::from("lost connection").into();
+/// assert!(matches!(error, AppErrorNode::Other(_)));
+/// assert_eq!(error.to_string(), "app: lost connection");
+/// ```
+///
+/// ```compile_fail
+/// use hierrorchy::error_node;
+/// use std::error::Error;
+///
+/// error_node! { type AppErrorNode<.., std::fmt::Error> = "app" }
+/// ```
+///
+/// ## Lifted errors
+/// A variant written as `+<node>(<errors>)` wraps the error `node`, and also generates a
+/// [std::convert::From] implementation for each of the given `errors`, converting them into `node`
+/// first. This allows to use the `?` operator on the errors of a child node directly.
+/// ```
+/// use hierrorchy::{error_leaf, error_node};
+///
+/// #[error_leaf(message = format!("error child 1"))]
+/// pub struct ErrorChild1 {}
+///
+/// error_node! { type ChildErrorNode<ErrorChild1> = "child" }
+/// error_node! { type ParentErrorNode<+ChildErrorNode(ErrorChild1)> = "parent" }
+///
+/// fn failing() -> Result<(), ParentErrorNode> {
+///     Err(ErrorChild1 {})?
+/// }
+/// ```
+///
+/// The lifted errors can also be listed with the `#[lift(<errors>)]` attribute on the variant,
+/// which is equivalent: `#[lift(ErrorChild1)] ChildErrorNode`.
+///
+/// As the lifted errors are converted with the `From` implementations of `node`, an error lifted by
+/// `node` itself can be lifted again by its parent, so that the `?` operator works across any
+/// number of levels: `+ParentErrorNode(ErrorChild1)` in a grandparent node.
+///
+/// ## Variant attributes
+/// Each variant can be configured with attributes written before it. The available attributes are:
+///
+/// | attribute | Description |
+/// | --- | --- |
+/// | `#[guard(<predicate>)]` | Generates `try_from_<variant>(value) -> Result<Self, Inner>`, with the name of the variant in snake case, which wraps `value` only if `predicate` (a closure or function taking `&Inner` and returning `bool`) holds, and returns it back otherwise. |
+/// | `#[no_from]` | Skips the [std::convert::From] implementation for the wrapped error, e.g. when it conflicts with another implementation. |
+/// | `#[prefix(<prefix>)]` | Prints `prefix` (a string literal, or an expression implementing [std::fmt::Display] like a translation function call) before the source of the variant, instead of the prefix of the node. |
+/// | `#[suffix("<text>")]` | Appends `text` verbatim after the source when printing the variant. |
+/// | `#[display(<function>)]` | Prints the variant with `function` (a closure or function taking `&Inner` and `&mut Formatter`, and returning [std::fmt::Result]) instead of the prefix and the source, e.g. to redact the wrapped error. |
+/// | `#[source(<accessor>)]` | Uses `value<accessor>` (e.g. `#[source(.inner())]`) as the source of the variant, instead of the wrapped value itself. The wrapped type does not need to be an error, but the accessor must return a reference to one. |
+/// | `#[owned]` | Generates a [std::convert::From] implementation for references to the wrapped error as well, which stores a clone of the error, so that a borrowed error can be wrapped in the node (which must own its errors to return them as sources). The wrapped error must implement [Clone]. |
+/// | `#[arc]` | Stores the wrapped error in an [std::sync::Arc], so that cloning the node (e.g. with `#[derive(Clone)]`) does not copy the error. The node can also be converted from an `Arc` of the wrapped error. |
+/// | `#[lift(<errors>)]` | Generates a [std::convert::From] implementation for each of the given `errors`, converting them into the wrapped error first (see [lifted errors](#lifted-errors)). |
+/// | `#[accepts(<types>)]` | Generates a [std::convert::From] implementation for each of the given `types`, converting them into the wrapped error with [std::convert::Into]. This allows to use the `?` operator on any error the wrapped error can be built from. |
+/// | `#[terminal]` | Returns `None` from [std::error::Error::source] for the variant, so that the source chain ends at the node and the errors below the wrapped one are hidden. The wrapped error is still printed after the prefix; with `display(numbered)`, it is numbered as a single level. |
+///
+/// Unknown attributes, on the node or on its variants, are rejected and the error lists the
+/// available ones:
+/// ```compile_fail
+/// use hierrorchy::{error_leaf, error_node};
+///
+/// #[error_leaf(message = format!("error child 1"))]
+/// pub struct ErrorChild1 {}
+///
+/// error_node! { type MyErrorNode<#[suffx(" (retrying)")] ErrorChild1> }
+/// ```
+///
+/// ```
+/// use hierrorchy::{error_leaf, error_node};
+///
+/// #[error_leaf(message = format!("read {} bytes", self.bytes))]
+/// pub struct ReadError {
+///     bytes: usize,
+/// }
+///
+/// error_node! { type MyErrorNode<#[guard(|e| e.bytes == 0)] ReadError> }
+///
+/// assert!(MyErrorNode::try_from_variant0(ReadError { bytes: 0 }).is_ok());
+/// assert!(MyErrorNode::try_from_variant0(ReadError { bytes: 8 }).is_err());
+/// ```
+///
+/// The wrapped error itself must not be listed in `accepts`, as it is already converted:
+/// ```compile_fail
+/// use hierrorchy::{error_leaf, error_node};
+///
+/// #[error_leaf(message = format!("error child 1"))]
+/// pub struct ErrorChild1 {}
+///
+/// error_node! { type MyErrorNode<#[accepts(ErrorChild1)] ErrorChild1> }
+/// ```
+///
+/// Likewise, a type can be accepted by only one variant, and cannot be accepted by a variant
+/// while being wrapped by another one, as its conversion would be ambiguous:
+/// ```compile_fail
+/// use hierrorchy::{error_leaf, error_node};
+///
+/// #[error_leaf(message = format!("error child 1"))]
+/// pub struct ErrorChild1 {}
+///
+/// impl From<&str> for ErrorChild1 {
+///     fn from(_: &str) -> Self {
+///         ErrorChild1 {}
+///     }
+/// }
+///
+/// #[error_leaf(message = format!("error child 2"))]
+/// pub struct ErrorChild2 {}
+///
+/// impl From<&str> for ErrorChild2 {
+///     fn from(_: &str) -> Self {
+///         ErrorChild2 {}
+///     }
+/// }
+///
+/// error_node! { type MyErrorNode<#[accepts(&str)] ErrorChild1, #[accepts(&str)] ErrorChild2> }
+/// ```
+///
+/// For the same reason, a type can be wrapped by several variants, e.g. to tell apart where an
+/// error comes from, only if all of them but one are marked with `#[no_from]`:
+/// ```
+/// use hierrorchy::error_node;
+///
+/// error_node! { type FileErrorNode<Read = std::io::Error, #[no_from] Write = std::io::Error> = "file" }
+///
+/// let error: FileErrorNode = std::io::Error::other("broken pipe").into();
+/// assert!(matches!(error, FileErrorNode::Read(_)));
+/// ```
+///
+/// ```compile_fail
+/// use hierrorchy::error_node;
+///
+/// error_node! { type FileErrorNode<std::io::Error, std::io::Error> = "file" }
+/// ```
+///
+/// ## Generic nodes
+/// Generic parameters, with their bounds, can be declared in a first angle-bracketed list before
+/// the variants, and an optional `where` clause can follow the variants:
+/// `type (name)<generics><variants> [where (predicates)] [= (string)]`.
+///
+/// The parameters are used for the enum and for all the generated implementations; type
+/// parameters also get a `'static` bound in the `Display` and `Error` implementations, as sources
+/// must be `'static`.
+/// ```
+/// use hierrorchy::{error_leaf, error_node};
+/// use std::error::Error;
+///
+/// #[error_leaf(message = format!("wrapped: {}", self.inner))]
+/// pub struct Wrap<T: Error> {
+///     inner: T,
+/// }
+///
+/// #[error_leaf(message = format!("plain error"))]
+/// pub struct PlainErr {}
+///
+/// error_node! { type E<T: Error + Send><Wrap<T>, PlainErr> = "generic node" }
+/// error_node! { type F<T><Wrap<T>, PlainErr> where T: Error + Send = "generic node" }
+/// ```
+///
+/// ## Display options
+/// The display of the node can be configured with the `#[display(...)]` attribute before the node
+/// declaration. The available options are:
+///
+/// | option | Description |
+/// | --- | --- |
+/// | `cache_prefix` | Evaluates the prefix expression only once, storing the result in a `OnceLock`. Requires the prefix to be an expression. |
+/// | `numbered` | Prefixes each level of the error chain with its number, e.g. `[1] outer: [2] inner: [3] leaf` (see [`hierrorchy::write_numbered_chain`](https://docs.rs/hierrorchy/latest/hierrorchy/fn.write_numbered_chain.html)). Meant for the outermost node of a hierarchy. |
+/// | `transparent` | Writes only the message of the wrapped error, without a prefix. Cannot be used with a prefix nor with `numbered`. |
+/// | `raw_prefix` | Separates the prefix from the source with a single space instead of `: `, e.g. for a prefix already ending with a colon. |
+/// | `separator = "<separator>"` | Separates the prefix from the source with the given string instead of `: `. Cannot be used with `raw_prefix` nor `transparent`. |
+///
+/// The default separator is the
+/// [`hierrorchy::DEFAULT_NODE_SEP`](https://docs.rs/hierrorchy/latest/hierrorchy/constant.DEFAULT_NODE_SEP.html)
+/// constant, `: `. A crate defining a `DEFAULT_NODE_SEP: &str` constant at its root uses it instead
+/// for every node, including the prefixes joined by [`error_nodes`](macro@error_nodes) and the
+/// nodes derived with [`ErrorNode`](derive@ErrorNode); the `separator` and `raw_prefix` options of a
+/// node take precedence over it.
+/// ```
+/// use hierrorchy::{error_leaf, error_node};
+///
+/// const DEFAULT_NODE_SEP: &str = " — ";
+///
+/// #[error_leaf(message = format!("disk is full"))]
+/// pub struct DiskFullError {}
+///
+/// error_node! { type StorageErrorNode<DiskFullError> = "storage" }
+/// error_node! { #[display(separator = " / ")] type CacheErrorNode<DiskFullError> = "cache" }
+///
+/// # fn main() {
+/// let error: StorageErrorNode = DiskFullError {}.into();
+/// assert_eq!(error.to_string(), "storage — disk is full");
+/// let error: CacheErrorNode = DiskFullError {}.into();
+/// assert_eq!(error.to_string(), "cache / disk is full");
+/// # }
+/// ```
+///
+/// As a transparent node displays the same message as its wrapped error, walking the source chain
+/// reports that message twice. With `#[source(collapse)]` as well, the node reports the source of
+/// the wrapped error as its own source, skipping the wrapped error in the chain.
+///
+/// ```
+/// use hierrorchy::{error_leaf, error_node};
+///
+/// fn translate(key: &str) -> String {
+///     format!("translation of {}", key)
+/// }
+///
+/// #[error_leaf(message = format!("error child 1"))]
+/// pub struct ErrorChild1 {}
+///
+/// error_node! {
+///     #[display(cache_prefix)]
+///     type MyErrorNode<ErrorChild1> = translate("my_error_node")
+/// }
+/// ```
+///
+/// ## Documentation
+/// Doc comments and `#[doc(...)]` attributes (like `#[doc(alias = "...")]`) before the node
+/// declaration are emitted unchanged on the enum. Each variant is documented with the type of the
+/// error it wraps, so nodes can be declared in crates denying `missing_docs`.
+/// ```
+/// #![deny(missing_docs)]
+/// //! A documented crate.
+/// use hierrorchy::{error_leaf, error_node};
+///
+/// /// The disk is full.
+/// #[error_leaf(message = format!("disk is full"))]
+/// pub struct DiskFullError {}
+///
+/// error_node! {
+///     /// The errors of the storage layer.
+///     #[doc(alias = "DiskError")]
+///     pub type StorageErrorNode<DiskFullError> = "storage"
+/// }
+/// # fn main() {}
+/// ```
+///
+/// ## Debug options
+/// With `#[debug(pretty)]` before the node declaration, the derived [std::fmt::Debug]
+/// implementation is replaced by one which prints the variant qualified with the node name (e.g.
+/// `MyErrorNode::Variant0(..)`) and, when the alternate flag is used (`{:#?}`), prints the wrapped
+/// error on its own indented lines.
+///
+/// With `#[no_debug]`, no [std::fmt::Debug] implementation is generated, e.g. when a wrapped type
+/// does not implement it: as [std::error::Error] requires [std::fmt::Debug], the node must
+/// implement it manually.
+/// ```
+/// use hierrorchy::{error_leaf, error_node};
+///
+/// #[error_leaf(message = format!("error child 1"))]
+/// pub struct ErrorChild1 {}
+///
+/// error_node! { #[no_debug] type MyErrorNode<ErrorChild1> }
+///
+/// impl std::fmt::Debug for MyErrorNode {
+///     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+///         f.write_str("MyErrorNode")
+///     }
+/// }
+/// ```
+///
+/// ## Derives
+/// With `#[derive(PartialEq)]` before the node declaration, [PartialEq] is derived on the node: two
+/// nodes are equal when they hold the same variant with equal errors. Every wrapped error must be
+/// `PartialEq`, which is checked on the declaration of the node.
+/// ```compile_fail
+/// use hierrorchy::{error_leaf, error_node};
+///
+/// #[error_leaf(message = format!("disk is full"))]
+/// pub struct DiskFullError {}
+///
+/// error_node! { #[derive(PartialEq)] type MyErrorNode<DiskFullError> }
+/// ```
+///
+/// [Clone] can be derived with `#[derive(Clone)]` as well, when every wrapped error is `Clone`.
+/// [Copy] cannot be derived, as every variant of a node wraps an error, so `#[derive(Copy)]` is
+/// rejected.
+/// ```compile_fail
+/// use hierrorchy::{error_leaf, error_node};
+///
+/// #[error_leaf(message = format!("disk is full"))]
+/// #[derive(Clone, Copy)]
+/// pub struct DiskFullError {}
+///
+/// error_node! { #[derive(Copy, Clone)] type MyErrorNode<DiskFullError> }
+/// ```
+///
+/// The other derives, like [Eq], [Hash] or the derives of other crates, are emitted
+/// unchanged on the enum, and require the wrapped errors to implement the derived traits. [Debug]
+/// is always derived, unless `#[no_debug]` is given, so it cannot be listed.
+/// ```
+/// use hierrorchy::{error_leaf, error_node};
+/// use std::collections::HashSet;
+///
+/// #[error_leaf(message = format!("disk is full"), hashable)]
+/// pub struct DiskFullError {}
+///
+/// error_node! { #[derive(PartialEq, Eq, Hash)] type MyErrorNode<DiskFullError> }
+///
+/// let errors: HashSet<MyErrorNode> = [DiskFullError {}.into(), DiskFullError {}.into()].into();
+/// assert_eq!(errors.len(), 1);
+/// ```
+///
+/// With `#[derive(Default)]`, [Default] is implemented on the node, building the variant given with
+/// `#[default = <variant name>]` from the default value of its error, which must implement [Default].
+/// ```
+/// use hierrorchy::{error_leaf, error_node};
+///
+/// #[error_leaf(message = format!("disk is full"))]
+/// pub struct DiskFullError {}
+///
+/// #[error_leaf(message = format!("unknown error"))]
+/// #[derive(Default)]
+/// pub struct UnknownError {}
+///
+/// error_node! { #[derive(Default)] #[default = Variant1] type MyErrorNode<DiskFullError, UnknownError> }
+///
+/// assert!(matches!(MyErrorNode::default(), MyErrorNode::Variant1(_)));
+/// ```
+///
+/// With `#[derive(PartialOrd, Ord)]`, the nodes are ordered by the declaration order of their
+/// variants, ignoring the wrapped errors, which do not need to be comparable: a node holding an
+/// earlier variant is less than a node holding a later one. For consistency, [PartialEq] and [Eq]
+/// are implemented in the same way, so two nodes holding the same variant are equal, and
+/// `derive(PartialEq)` cannot be used as well. `PartialOrd` cannot be derived without `Ord`.
+/// ```
+/// use hierrorchy::{error_leaf, error_node};
+///
+/// #[error_leaf(message = format!("disk is full"))]
+/// pub struct DiskFullError {}
+///
+/// #[error_leaf(message = format!("disk is slow"))]
+/// pub struct DiskSlowError {}
+///
+/// error_node! { #[derive(PartialOrd, Ord)] type MyErrorNode<DiskFullError, DiskSlowError> }
+///
+/// assert!(MyErrorNode::from(DiskFullError {}) < MyErrorNode::from(DiskSlowError {}));
+/// ```
+///
+/// ## Kind strings
+/// With `#[kind_str]` before the node declaration, a `kind_str(&self) -> &'static str` method is
+/// generated, which returns the `kind_str()` of the wrapped error. Every variant must therefore
+/// have a `kind_str` method, like leaves declared with the `kind_str` keyword or other nodes
+/// declared with `#[kind_str]`.
+/// ```
+/// use hierrorchy::{error_leaf, error_node};
+///
+/// #[error_leaf(message = format!("disk is full"), kind_str = "disk_full")]
+/// pub struct DiskFullError {}
+///
+/// error_node! { #[kind_str] type MyErrorNode<DiskFullError> }
+///
+/// let error: MyErrorNode = DiskFullError {}.into();
+/// assert_eq!(error.kind_str(), "disk_full");
+/// ```
+///
+/// ## Severity
+/// With `#[severity]` before the node declaration, the `severity(&self) -> hierrorchy::Severity`
+/// and `is_at_least(&self, severity) -> bool` methods are generated, which forward to the wrapped
+/// error if it implements `hierrorchy::HasSeverity`, like leaves and other nodes declared with
+/// `#[severity]` do, and return `Severity::Error` otherwise.
+/// ```
+/// use hierrorchy::{error_leaf, error_node, Severity};
+/// use std::error::Error;
+///
+/// #[error_leaf(message = format!("disk is full"), severity = Fatal)]
+/// pub struct DiskFullError {}
+///
+/// error_node! { #[severity] type MyErrorNode<DiskFullError> }
+///
+/// let error: MyErrorNode = DiskFullError {}.into();
+/// assert_eq!(error.severity(), Severity::Fatal);
+/// assert!(error.is_at_least(Severity::Error));
+/// ```
+///
+/// ## Kind enum
+/// A `#[repr(...)]` attribute with an integer type before the node declaration generates a
+/// companion `<name>Kind` enum, with the given representation and a fieldless variant for each
+/// variant of the node. Variants can be assigned an explicit discriminant with `<variant> = <integer>` (or
+/// `<name> = <variant> = <integer>` for a named variant).
+///
+/// The methods `kind()`, returning the kind of the node, `tag()`, returning the discriminant of
+/// its kind, and `is_kind(kind)`, checking the kind of the node, are generated as well. The
+/// associated function `from_dyn(err: &(dyn Error + 'static)) -> Option<Kind>` returns the kind of
+/// the first variant wrapping the concrete type of an erased error, which helps handling an error
+/// received as `&dyn Error` by its type again.
+/// ```
+/// use hierrorchy::{error_leaf, error_node};
+/// use std::error::Error;
+///
+/// #[error_leaf(message = format!("error child 1"))]
+/// pub struct ErrorChild1 {}
+///
+/// #[error_leaf(message = format!("error child 2"))]
+/// pub struct ErrorChild2 {}
+///
+/// error_node! { #[repr(u8)] type MyErrorNode<ErrorChild1 = 1, ErrorChild2 = 2> }
+///
+/// let error: MyErrorNode = ErrorChild2 {}.into();
+/// assert_eq!(error.kind(), MyErrorNodeKind::Variant1);
+/// assert_eq!(error.tag(), 2);
+/// assert!(error.is_kind(MyErrorNodeKind::Variant1));
+///
+/// let erased: &(dyn Error + 'static) = &ErrorChild1 {};
+/// assert_eq!(MyErrorNode::from_dyn(erased), Some(MyErrorNodeKind::Variant0));
+/// ```
+///
+/// ## Log lines
+/// With `#[log_line]` before the node declaration, a `to_log_line(&self) -> String` method is
+/// generated, which returns the kind, the code and the message of the node separated by `|`. The
+/// kind is the one returned by `kind_str()` with `#[kind_str]`, and the code is the one returned by
+/// `tag()` with `#[repr(...)]`; otherwise they are empty.
+/// ```
+/// use hierrorchy::{error_leaf, error_node};
+///
+/// #[error_leaf(message = format!("disk is full"), kind_str = "disk_full")]
+/// pub struct DiskFullError {}
+///
+/// error_node! { #[log_line] #[kind_str] #[repr(u8)] type MyErrorNode<DiskFullError = 7> = "storage" }
+///
+/// let error: MyErrorNode = DiskFullError {}.into();
+/// assert_eq!(error.to_log_line(), "disk_full|7|storage: disk is full");
+/// ```
+///
+/// ## Infallible conversions
+/// With `#[from_infallible]` before the node declaration, `From<Infallible>` is implemented for
+/// the node, so that the `?` operator can be used on a `Result<T, Infallible>` in a function
+/// returning the node, e.g. in generic code where some conversions cannot fail.
+/// ```
+/// use hierrorchy::{error_leaf, error_node};
+///
+/// #[error_leaf(message = format!("disk is full"))]
+/// pub struct DiskFullError {}
+///
+/// error_node! { #[from_infallible] type MyErrorNode<DiskFullError> = "storage" }
+///
+/// fn to_u64(value: u32) -> Result<u64, MyErrorNode> {
+///     Ok(u64::try_from(value)?)
+/// }
+/// ```
+///
+/// ## Result alias
+/// With `#[result]` before the node declaration, a `<name>Result<T>` alias of
+/// `Result<T, <name>>` is generated, with the visibility of the node. The generic parameters of a
+/// generic node follow `T`, so that the node cannot have a parameter named `T` itself.
+/// ```
+/// use hierrorchy::{error_leaf, error_node};
+///
+/// #[error_leaf(message = format!("disk is full"))]
+/// pub struct DiskFullError {}
+///
+/// error_node! { #[result] pub type StorageErrorNode<DiskFullError> = "storage" }
+///
+/// fn write_file() -> StorageErrorNodeResult<usize> {
+///     Err(DiskFullError {})?
+/// }
+///
+/// assert_eq!(write_file().unwrap_err().to_string(), "storage: disk is full");
+/// ```
+///
+/// ## Unique prefixes
+/// With `#[unique_prefix]` before the node declaration, the prefix of the node is registered as a
+/// hidden constant named after it, so that two nodes declaring the same prefix with the attribute
+/// in the same module do not compile, reporting the constant as defined multiple times. This is a
+/// best-effort guard for consistent logs: nodes in different modules, or without the attribute,
+/// are not checked. The prefix must be a string literal.
+/// ```
+/// use hierrorchy::{error_leaf, error_node};
+///
+/// #[error_leaf(message = format!("disk is full"))]
+/// pub struct DiskFullError {}
+///
+/// error_node! { #[unique_prefix] type StorageErrorNode<DiskFullError> = "storage" }
+/// error_node! { #[unique_prefix] type DiskErrorNode<DiskFullError> = "disk" }
+/// ```
+///
+/// ```compile_fail
+/// use hierrorchy::{error_leaf, error_node};
+///
+/// #[error_leaf(message = format!("disk is full"))]
+/// pub struct DiskFullError {}
+///
+/// error_node! { #[unique_prefix] type StorageErrorNode<DiskFullError> = "storage" }
+/// error_node! { #[unique_prefix] type DiskErrorNode<DiskFullError> = "storage" }
+/// ```
+///
+/// ## Backtraces
+/// With `#[backtrace]` before the node declaration, each variant holds a
+/// [std::backtrace::Backtrace] after the wrapped error, captured with
+/// [std::backtrace::Backtrace::capture] when the node is created from it (e.g. by the `?`
+/// operator), and returned by the generated `backtrace()` method. This is meant for the outermost
+/// node, recording where the error crossed into it; as for any captured backtrace, it is only
+/// collected when enabled by the `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE` environment variables. A
+/// `#[backtrace]` node cannot derive [PartialEq] nor [Clone], nor be erased. On a nightly toolchain,
+/// with `RUSTFLAGS="--cfg hierrorchy_provide"` (see [`error_leaf`](macro@error_leaf)), the node
+/// also provides the backtrace with [std::error::Error::provide], so that it can be requested
+/// with `std::error::request_ref::<Backtrace>`.
+/// ```
+/// use hierrorchy::{error_leaf, error_node};
+///
+/// #[error_leaf(message = format!("disk is full"))]
+/// pub struct DiskFullError {}
+///
+/// error_node! { #[backtrace] type MyErrorNode<DiskFullError> = "storage" }
+///
+/// fn write_file() -> Result<(), MyErrorNode> {
+///     Err(DiskFullError {})?
+/// }
+///
+/// let error = write_file().unwrap_err();
+/// println!("{}", error.backtrace());
+/// if let MyErrorNode::Variant0(err, _backtrace) = error {
+///     assert_eq!(err.to_string(), "disk is full");
+/// }
+/// ```
+///
+/// ## Context
+/// With `#[with_context(Type)]` before the node declaration, each variant holds a value of the
+/// given type after the wrapped error (and before the backtrace of a `#[backtrace]` node), returned
+/// by the generated `context()` method. The conversions of the node then take a `(context, error)`
+/// tuple instead of the error alone, so that the context is given with
+/// `.map_err(|err| (context, err))?`. The context is not part of the message of the node. A
+/// `#[with_context(...)]` node cannot have a `#[default = ...]` variant, guarded variants, nor be
+/// erased.
+/// ```
+/// use hierrorchy::{error_leaf, error_node};
+///
+/// #[error_leaf(message = format!("disk is full"))]
+/// pub struct DiskFullError {}
+///
+/// error_node! { #[with_context(String)] type MyErrorNode<DiskFullError> = "storage" }
+///
+/// fn write_file(path: &str) -> Result<(), MyErrorNode> {
+///     Err(DiskFullError {}).map_err(|err| (path.to_string(), err))?
+/// }
+///
+/// let error = write_file("/tmp/data").unwrap_err();
+/// assert_eq!(error.context(), "/tmp/data");
+/// assert_eq!(error.to_string(), "storage: disk is full");
+/// ```
+///
+/// ## Erased nodes
+/// With `#[erased]` before the node declaration, the node is generated as a struct holding the
+/// kind of the wrapped error and the error itself, boxed as `Box<dyn Error + Send + Sync>`, instead
+/// of an enum. The kind is a fieldless `<Name>Kind` enum with a variant for each wrapped error, as
+/// the [kind enum](#kind-enum), and is returned by `kind()`; `is_kind(kind)`, `from_dyn(err)`, `inner()`,
+/// `into_inner()`, `chain_messages()`, `sources()` and `root_cause()` are generated as well. The
+/// node is printed as an enum node, but cannot be matched exhaustively on the wrapped error, and
+/// its size does not depend on the size of the wrapped errors.
+///
+/// The wrapped errors must be `Send + Sync`. An erased node cannot be generic, nor have variant
+/// attributes, and only the `display(numbered)`, `display(cache_prefix)`, `display(raw_prefix)`,
+/// `display(separator = ...)`, `no_debug`, `cache_root_cause` and documentation attributes can be
+/// used with it.
+/// ```
+/// use hierrorchy::{error_leaf, error_node};
+///
+/// #[error_leaf(message = format!("disk is full"))]
+/// pub struct DiskFullError {}
+///
+/// error_node! { #[erased] type MyErrorNode<DiskFullError> = "storage" }
+///
+/// let error: MyErrorNode = DiskFullError {}.into();
+/// assert_eq!(error.kind(), MyErrorNodeKind::Variant0);
+/// assert_eq!(error.to_string(), "storage: disk is full");
+/// ```
+///
+/// With `#[cache_root_cause]` as well, the node looks for the root cause of its source chain only
+/// on the first call to `root_cause()`, and returns the same error on the following calls without
+/// walking the chain again. It is only supported on erased nodes.
+/// ```
+/// use hierrorchy::{error_leaf, error_node};
+///
+/// #[error_leaf(message = format!("disk is full"))]
+/// pub struct DiskFullError {}
+///
+/// error_node! { #[erased] #[cache_root_cause] type MyErrorNode<DiskFullError> = "storage" }
+///
+/// let error: MyErrorNode = DiskFullError {}.into();
+/// assert!(error.root_cause().is::<DiskFullError>());
+/// ```
+///
+/// ```compile_fail
+/// use hierrorchy::{error_leaf, error_node};
+///
+/// #[error_leaf(message = format!("disk is full"))]
+/// pub struct DiskFullError {}
+///
+/// error_node! { #[cache_root_cause] type MyErrorNode<DiskFullError> = "storage" }
+/// ```
+///
+/// ## Generated methods
+/// Besides the trait implementations, the following inherent methods and constants are generated on
+/// the node:
+///
+/// | method | Description |
+/// | --- | --- |
+/// | `VARIANT_TYPES: &'static [&'static str]` | An associated constant with the name of the error type wrapped by each variant, in declaration order, e.g. to build a catalog of the errors. |
+/// | `backtrace(&self) -> &std::backtrace::Backtrace` | Only with `#[backtrace]`: the backtrace captured when the node was created (see [backtraces](#backtraces)). |
+/// | `is_retryable(&self) -> bool` | Whether the wrapped error is retryable, i.e. it implements [`hierrorchy::Retryable`](https://docs.rs/hierrorchy/latest/hierrorchy/trait.Retryable.html) (like the leaves declared with `retryable` and the nodes) and returns `true`. A wrapped error which does not implement it is not retryable. The node implements `Retryable` as well. Not generated on erased nodes. |
+/// | `as_parts(&self) -> (u32, String)` | The code of the wrapped error, i.e. the one returned by [`hierrorchy::Coded`](https://docs.rs/hierrorchy/latest/hierrorchy/trait.Coded.html) (like for the leaves declared with `code` and the nodes), or 0 if it does not implement it, and the message of the node, e.g. for FFI or serialization boundaries. The node implements `Coded` as well. Not generated on erased nodes. |
+/// | `inner(&self) -> &(dyn Error + 'static)` | The error wrapped by the active variant. Unlike [std::error::Error::source], it does not return an [Option], as every variant wraps an error. |
+/// | `chain_messages(&self) -> Vec<String>` | The message of each level of the error chain, from the node to the deepest source. |
+/// | `root_cause(&self) -> &(dyn Error + 'static)` | The deepest error of the source chain, e.g. the leaf wrapped by the innermost node. |
+/// | `detailed(&self) -> String` | A multi-line description of the node, for logs: the first line is like `MyErrorNode::Variant0 [kind: quota, code: 402]: <message>`, where the kind and the code are printed only with `#[kind_str]` and `#[repr(...)]` respectively, and each following line is `  caused by: <message>` for an error of the source chain. |
+/// | `sources(&self) -> hierrorchy::Sources` | An iterator over the node and the errors in its source chain (see [`hierrorchy::sources`](https://docs.rs/hierrorchy/latest/hierrorchy/fn.sources.html)). |
+/// | `to_json(&self) -> serde_json::Value` | Only with the `serde_json` feature: converts the node into an object like `{ "type": "MyErrorNode", "variant": "Variant0", "message": "...", "source": { ... } }`, where the source is converted recursively when it is a node as well, and is `{ "message": "..." }` otherwise. The `serde_json` feature of `hierrorchy` re-exports `serde_json` for the generated code, so the crate using the macro does not need to depend on it. The [`hierrorchy::ToJson`](https://docs.rs/hierrorchy/latest/hierrorchy/trait.ToJson.html) trait is implemented as well. |
+/// | `context_anyhow(self, msg: &str) -> anyhow::Error` | Only with the `anyhow` feature: converts the node into an `anyhow::Error` with `msg` as additional context. The `anyhow` feature of `hierrorchy` re-exports `anyhow` for the generated code, so the crate using the macro does not need to depend on it. The node must be `Send + Sync`. |
+///
+#[proc_macro]
+pub fn error_node(tokens: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(tokens as ErrorNode);
+
+    input.to_token_stream()
+}
+
+/// Function-like proc macro to construct several error nodes sharing a common prefix.
+/// The body requires the following format:
+/// `prefix (string); (node); (node); ...`
+/// where each `node` is declared as in [`error_node!`], and `string` is prepended to the prefix of
+/// each node (or to its name, if it has no prefix), separated by `: `.
+///
+/// # Examples
+/// ```
+/// use hierrorchy::{error_leaf, error_nodes};
+///
+/// #[error_leaf(message = format!("error child 1"))]
+/// pub struct ErrorChild1 {}
+///
+/// #[error_leaf(message = format!("error child 2"))]
+/// pub struct ErrorChild2 {}
+///
+/// error_nodes! {
+///     prefix "app";
+///     type FirstNode<ErrorChild1>;
+///     type SecondNode<ErrorChild2> = "second";
+/// }
+///
+/// let error: FirstNode = ErrorChild1 {}.into();
+/// assert_eq!(error.to_string(), "app: FirstNode: error child 1");
+/// let error: SecondNode = ErrorChild2 {}.into();
+/// assert_eq!(error.to_string(), "app: second: error child 2");
+/// ```
+#[proc_macro]
+pub fn error_nodes(tokens: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(tokens as ErrorNodes);
+
+    input.to_token_stream()
+}
+
+/// Function-like proc macro to declare a whole tree of errors in one block, generating each of its
+/// leaves and nodes.
+/// The body requires the following format:
+/// `[(visibility)] (name) [= (prefix)] { (children) }`
+/// where the root node is declared as in the [brace form](macro@error_node#brace-form) of
+/// [`error_node!`], and each child is one of:
+/// - a nested node, declared in the same way;
+/// - `(name)("message")`, a leaf declared as with [`error_leaf`](macro@error_leaf) with a
+///   constant message;
+/// - `(name)(error)`, an error declared elsewhere, like `std::io::Error`;
+/// - `(error)`, an error declared elsewhere, like another leaf.
+///
+/// Each child is wrapped by a variant of its node named after it (after the last segment of the
+/// path of `error` for the last form). Attributes can be written before the nested nodes, where
+/// they configure the node as in [`error_node!`], and before the leaves, where they are emitted on
+/// the struct. The leaves and the nodes have the visibility given before the root, e.g. `pub` or
+/// `pub(crate)`.
+///
+/// # Examples
+/// ```
+/// use hierrorchy::{error_hierarchy, error_leaf};
+/// use std::error::Error;
+///
+/// #[error_leaf(message = format!("unexpected end of file"))]
+/// pub struct EofError {}
+///
+/// error_hierarchy! {
+///     pub AppError = "app" {
+///         Parse = "parse" { BadToken("bad token"), EofError },
+///         Io(std::io::Error),
+///     }
+/// }
+///
+/// fn parse() -> Result<(), Parse> {
+///     Err(BadToken {})?
+/// }
+///
+/// let error: AppError = parse().unwrap_err().into();
+/// assert!(matches!(error, AppError::Parse(Parse::BadToken(_))));
+/// assert_eq!(error.to_string(), "app: parse: bad token");
+/// ```
+#[proc_macro]
+pub fn error_hierarchy(tokens: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(tokens as ErrorHierarchy);
+
+    input.to_token_stream()
+}
+
+/// Derive macro to turn an existing enum into an error node, keeping its variant names and
+/// additional data.
+///
+/// The [std::fmt::Display] implementation prints the prefix of the node followed by the source,
+/// separated by `: ` as in [`error_node!`]. The prefix is given with
+/// `#[error_node(prefix = "...")]` on the enum, and defaults to its name.
+///
+/// Each variant wraps its source: the field of a variant with a single field, or the field marked
+/// with `#[source]` otherwise. The source is returned by [std::error::Error::source], and a
+/// [std::convert::From] implementation is generated for the variants with a single field.
+///
+/// # Examples
+/// ```
+/// use hierrorchy::{error_leaf, ErrorNode};
+/// use std::error::Error;
+///
+/// #[error_leaf(message = format!("invalid syntax"))]
+/// pub struct ParseError {}
+///
+/// #[derive(Debug, ErrorNode)]
+/// #[error_node(prefix = "config")]
+/// pub enum ConfigError {
+///     Parse(ParseError),
+///     Read { path: String, #[source] cause: std::io::Error },
+/// }
+///
+/// let error: ConfigError = ParseError {}.into();
+/// assert_eq!(error.to_string(), "config: invalid syntax");
+///
+/// let error = ConfigError::Read { path: String::from("app.toml"), cause: std::io::Error::other("denied") };
+/// assert_eq!(error.source().unwrap().to_string(), "denied");
+/// ```
+///
+/// A variant with several fields and none marked with `#[source]` is rejected:
+/// ```compile_fail
+/// use hierrorchy::ErrorNode;
+///
+/// #[derive(Debug, ErrorNode)]
+/// pub enum ConfigError {
+///     Read { path: String, cause: std::io::Error },
+/// }
+/// ```
+#[proc_macro_derive(ErrorNode, attributes(error_node, source))]
+pub fn derive_error_node(tokens: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(tokens as DeriveInput);
+    match ErrorNodeDerive::new(input) {
+        Ok(node) => node.to_token_stream(),
+        Err(e) => e.to_compile_error().into(),
+    }
+}
+
+/// Function-like proc macro to convert an error node into another one, re-wrapping each error of the
+/// first node.
+/// The body requires the following format:
+/// `from (source)(variants) into (target)`
+/// where `source` is the node to convert, `variants` is the comma-separated list of the variants
+/// of `source`, in the same order as its declaration, and `target` is the node to convert into.
+///
+/// The variants are written as in the declaration of `source`: a variant with a name, like the
+/// ones of the nodes generated by [`error_hierarchy!`], must be written as `(name) = (error)`,
+/// while an unnamed variant is written as `(error)` and matched by its position.
+///
+/// A [std::convert::From] implementation is generated from `source` to `target`, which converts
+/// the error of each variant with the existing `From` implementations of `target`: `target` must
+/// therefore implement `From` for each error in `variants`, for example by declaring them as
+/// variants.
+///
+/// # Examples
+/// ```
+/// use hierrorchy::{error_bridge, error_leaf, error_node};
+///
+/// #[error_leaf(message = format!("error child 1"))]
+/// pub struct ErrorChild1 {}
+///
+/// #[error_leaf(message = format!("error child 2"))]
+/// pub struct ErrorChild2 {}
+///
+/// error_node! { type SourceNode<ErrorChild1, ErrorChild2> = "source" }
+/// error_node! { type TargetNode<ErrorChild2, ErrorChild1> = "target" }
+///
+/// error_bridge! { from SourceNode(ErrorChild1, ErrorChild2) into TargetNode }
+///
+/// let error: TargetNode = SourceNode::from(ErrorChild1 {}).into();
+/// assert_eq!(error.to_string(), "target: error child 1");
+/// ```
+///
+/// ```
+/// use hierrorchy::{error_bridge, error_leaf, error_node};
+///
+/// #[error_leaf(message = format!("error child 1"))]
+/// pub struct ErrorChild1 {}
+///
+/// error_node! { type SourceNode<Child = ErrorChild1, Io = std::io::Error> = "source" }
+/// error_node! { type TargetNode<std::io::Error, ErrorChild1> = "target" }
+///
+/// error_bridge! { from SourceNode(Child = ErrorChild1, Io = std::io::Error) into TargetNode }
+///
+/// let error: TargetNode = SourceNode::from(ErrorChild1 {}).into();
+/// assert_eq!(error.to_string(), "target: error child 1");
+/// ```
+#[proc_macro]
+pub fn error_bridge(tokens: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(tokens as ErrorBridge);
+
+    input.to_token_stream()
+}
@@ -0,0 +1,91 @@
+/// Implemented by the functions returning a [Result], exposing its error type.
+///
+/// `Args` is the tuple of the argument types of the function, which is needed to implement the
+/// trait for functions of any arity. This trait is an implementation detail of
+/// [`assert_error_type`](crate::assert_error_type).
+#[doc(hidden)]
+pub trait ReturnsResult<Args> {
+    /// The error type of the returned [Result].
+    type Error;
+}
+
+macro_rules! impl_returns_result {
+    ($( $arg:ident ),*) => {
+        impl<F, T, E, $( $arg ),*> ReturnsResult<($( $arg, )*)> for F
+        where
+            F: Fn($( $arg ),*) -> Result<T, E>,
+        {
+            type Error = E;
+        }
+    };
+}
+
+impl_returns_result!();
+impl_returns_result!(A1);
+impl_returns_result!(A1, A2);
+impl_returns_result!(A1, A2, A3);
+impl_returns_result!(A1, A2, A3, A4);
+impl_returns_result!(A1, A2, A3, A4, A5);
+impl_returns_result!(A1, A2, A3, A4, A5, A6);
+impl_returns_result!(A1, A2, A3, A4, A5, A6, A7);
+impl_returns_result!(A1, A2, A3, A4, A5, A6, A7, A8);
+
+/// Asserts at compile time that a function returns a [Result] whose error type is the given one.
+///
+/// This is useful to guard the public API of a library against accidental changes of its error
+/// types. The function can have up to 8 arguments.
+///
+/// # Examples
+/// ```
+/// use hierrorchy::{assert_error_type, error_leaf, error_node};
+/// use std::error::Error;
+///
+/// #[error_leaf(message = format!("invalid port {}", self.port))]
+/// pub struct InvalidPortError {
+///     port: u32,
+/// }
+///
+/// error_node! { pub type ConfigErrorNode<InvalidPortError> = "invalid configuration" }
+///
+/// pub fn parse_port(port: u32) -> Result<u16, ConfigErrorNode> {
+///     u16::try_from(port).map_err(|_| InvalidPortError { port }.into())
+/// }
+///
+/// assert_error_type!(parse_port, ConfigErrorNode);
+/// ```
+///
+/// A different error type does not compile:
+/// ```compile_fail
+/// use hierrorchy::{assert_error_type, error_leaf, error_node};
+/// use std::error::Error;
+///
+/// #[error_leaf(message = format!("invalid port {}", self.port))]
+/// pub struct InvalidPortError {
+///     port: u32,
+/// }
+///
+/// error_node! { pub type ConfigErrorNode<InvalidPortError> = "invalid configuration" }
+///
+/// pub fn parse_port(port: u32) -> Result<u16, InvalidPortError> {
+///     u16::try_from(port).map_err(|_| InvalidPortError { port })
+/// }
+///
+/// assert_error_type!(parse_port, ConfigErrorNode);
+/// ```
+#[macro_export]
+macro_rules! assert_error_type {
+    ($function:expr, $error:ty $(,)?) => {
+        const _: () = {
+            fn assert_error_type<F, Args>(_: &F)
+            where
+                F: $crate::ReturnsResult<Args, Error = $error>,
+            {
+            }
+
+            #[allow(dead_code)]
+            fn check() {
+                assert_error_type(&$function);
+            }
+        };
+    };
+}
@@ -0,0 +1,62 @@
+use std::{error::Error, fmt, ptr::NonNull, sync::OnceLock};
+
+use crate::sources;
+
+/// The boxed error wrapped by an `#[erased]` node declared with `#[cache_root_cause]`, along with
+/// the root cause of its source chain, found on the first call to [`CachedSource::root_cause`].
+///
+/// This type is an implementation detail of [`error_node`](macro@crate::error_node).
+#[doc(hidden)]
+pub struct CachedSource {
+    source: Box<dyn Error + Send + Sync>,
+    root_cause: OnceLock<RootCause>,
+}
+
+/// A pointer to the root cause of the source chain of a [`CachedSource`].
+struct RootCause(NonNull<dyn Error + 'static>);
+
+// SAFETY: the pointer is only dereferenced into a shared reference by `CachedSource::root_cause`,
+// which any thread sharing the `CachedSource` could obtain by walking the source chain itself.
+unsafe impl Send for RootCause {}
+unsafe impl Sync for RootCause {}
+
+impl CachedSource {
+    /// Wraps the given error, without looking for its root cause yet.
+    pub fn new(source: Box<dyn Error + Send + Sync>) -> Self {
+        CachedSource {
+            source,
+            root_cause: OnceLock::new(),
+        }
+    }
+
+    /// Returns the wrapped error.
+    pub fn get(&self) -> &(dyn Error + Send + Sync + 'static) {
+        &*self.source
+    }
+
+    /// Returns the wrapped error, discarding the cached root cause.
+    pub fn into_inner(self) -> Box<dyn Error + Send + Sync> {
+        self.source
+    }
+
+    /// Returns the deepest error of the source chain of the wrapped error, walking the chain only
+    /// on the first call.
+    pub fn root_cause(&self) -> &(dyn Error + 'static) {
+        let root_cause = self.root_cause.get_or_init(|| {
+            let source: &(dyn Error + 'static) = &*self.source;
+            RootCause(NonNull::from(
+                sources(source).last().expect("the source chain contains at least the error"),
+            ))
+        });
+        // SAFETY: the root cause is borrowed from the boxed error, which is owned by `self` and
+        // never mutated nor moved out of its allocation while `self` is borrowed, as the fields of
+        // this type are private and `into_inner` consumes it.
+        unsafe { root_cause.0.as_ref() }
+    }
+}
+
+impl fmt::Debug for CachedSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.source, f)
+    }
+}
@@ -0,0 +1,147 @@
+use std::{error::Error, fmt};
+
+/// Writes the message of `err` and of each error in its source chain into `f`, separated by `sep`.
+///
+/// The function does not allocate, so it can be used inside custom [`std::fmt::Display`]
+/// implementations.
+///
+/// Note that the error nodes generated by [`error_node`](macro@crate::error_node) already print
+/// their source in their message, so this helper is mainly useful with errors whose message does
+/// not include their source.
+///
+/// # Examples
+/// ```
+/// use std::{error::Error, fmt};
+///
+/// use hierrorchy::write_chain;
+///
+/// #[derive(Debug)]
+/// struct Inner;
+///
+/// impl fmt::Display for Inner {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         write!(f, "inner")
+///     }
+/// }
+///
+/// impl Error for Inner {}
+///
+/// #[derive(Debug)]
+/// struct Outer(Inner);
+///
+/// impl fmt::Display for Outer {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         write!(f, "outer")
+///     }
+/// }
+///
+/// impl Error for Outer {
+///     fn source(&self) -> Option<&(dyn Error + 'static)> {
+///         Some(&self.0)
+///     }
+/// }
+///
+/// struct Chain<'a>(&'a dyn Error);
+///
+/// impl fmt::Display for Chain<'_> {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         write_chain(f, self.0, " -> ")
+///     }
+/// }
+///
+/// assert_eq!(Chain(&Outer(Inner)).to_string(), "outer -> inner");
+/// ```
+pub fn write_chain(f: &mut fmt::Formatter<'_>, err: &dyn Error, sep: &str) -> fmt::Result {
+    write!(f, "{}", err)?;
+    if let Some(source) = err.source() {
+        for source in sources(source) {
+            write!(f, "{}{}", sep, source)?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes `err` and each error in its source chain into `f`, prefixing each level with its number
+/// in square brackets, starting from `start`.
+///
+/// As the message of an error may already contain the message of its source (like the error nodes
+/// generated by [`error_node`](macro@crate::error_node)), the message of the source is removed
+/// from the end of the message of each level; levels whose message does not end with the one of
+/// their source are followed by `": "`.
+///
+/// Unlike [`write_chain`], this function allocates the message of each level.
+///
+/// # Examples
+/// ```
+/// use hierrorchy::{error_leaf, error_node, write_numbered_chain};
+/// use std::{error::Error, fmt};
+///
+/// #[error_leaf(message = format!("error child 1"))]
+/// pub struct ErrorChild1 {}
+///
+/// error_node! { type MyErrorNode<ErrorChild1> = "my error node" }
+///
+/// struct Numbered<'a>(&'a (dyn Error + 'static));
+///
+/// impl fmt::Display for Numbered<'_> {
+///     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+///         write_numbered_chain(f, self.0, 1)
+///     }
+/// }
+///
+/// let error: MyErrorNode = ErrorChild1 {}.into();
+/// assert_eq!(Numbered(&error).to_string(), "[1] my error node: [2] error child 1");
+/// ```
+pub fn write_numbered_chain(f: &mut fmt::Formatter<'_>, err: &(dyn Error + 'static), start: usize) -> fmt::Result {
+    let mut levels = sources(err).enumerate().peekable();
+    while let Some((index, level)) = levels.next() {
+        let message = level.to_string();
+        write!(f, "[{}] ", start + index)?;
+        match levels.peek() {
+            Some((_, source)) => match message.strip_suffix(&source.to_string()) {
+                Some(own_message) => f.write_str(own_message)?,
+                None => write!(f, "{}: ", message)?,
+            },
+            None => f.write_str(&message)?,
+        }
+    }
+    Ok(())
+}
+
+/// Returns an iterator over `err` and the errors in its source chain, starting from `err`.
+///
+/// This mirrors the unstable `Error::sources` method of the standard library.
+///
+/// # Examples
+/// ```
+/// use hierrorchy::{error_leaf, error_node, sources};
+/// use std::error::Error;
+///
+/// #[error_leaf(message = format!("error child 1"))]
+/// pub struct ErrorChild1 {}
+///
+/// error_node! { type MyErrorNode<ErrorChild1> = "my error node" }
+///
+/// let error: MyErrorNode = ErrorChild1 {}.into();
+/// let messages: Vec<String> = sources(&error).map(|it| it.to_string()).collect();
+/// assert_eq!(messages, vec!["my error node: error child 1", "error child 1"]);
+/// ```
+pub fn sources<'a>(err: &'a (dyn Error + 'static)) -> Sources<'a> {
+    Sources { current: Some(err) }
+}
+
+/// Iterator over an error and its source chain, created by [`sources`].
+#[derive(Debug, Clone)]
+pub struct Sources<'a> {
+    current: Option<&'a (dyn Error + 'static)>,
+}
+
+impl<'a> Iterator for Sources<'a> {
+    type Item = &'a (dyn Error + 'static);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.current?;
+        self.current = current.source();
+        Some(current)
+    }
+}
@@ -0,0 +1,24 @@
+/// Implemented by the errors which have a numeric code, e.g. to cross an FFI or serialization
+/// boundary.
+///
+/// It is implemented by the leaves declared with `code`, and by the error nodes which are not
+/// `#[erased]`, returning the code of their wrapped error. The nodes consider the code of the
+/// wrapped errors which do not implement this trait to be 0.
+///
+/// # Examples
+/// ```
+/// use hierrorchy::{error_leaf, error_node, Coded};
+/// use std::error::Error;
+///
+/// #[error_leaf(message = format!("not found"), code = 404)]
+/// struct NotFoundError {}
+///
+/// error_node! { type HttpErrorNode<NotFoundError, std::io::Error> = "http" }
+///
+/// let error: HttpErrorNode = NotFoundError {}.into();
+/// assert_eq!(Coded::code(&error), 404);
+/// ```
+pub trait Coded {
+    /// Returns the numeric code of this error.
+    fn code(&self) -> u32;
+}
@@ -0,0 +1,13 @@
+/// Implemented by the error nodes generated with the `serde_json` feature, to convert them into a
+/// JSON tree.
+///
+/// The value is generic, so that this trait is available without the feature: the nodes use
+/// `serde_json::Value`. When converting a node, the wrapped errors implementing this trait are
+/// converted recursively, while the others are converted into an object with their message only.
+pub trait ToJson {
+    /// The type of the JSON value.
+    type Value;
+
+    /// Converts the error into a JSON tree.
+    fn to_json(&self) -> Self::Value;
+}
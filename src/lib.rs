@@ -59,7 +59,6 @@
 //! Error nodes are declared by the function-like macro [`hierrorchy::error_node`](macro@error_node):
 //! ```
 //! use hierrorchy::{error_leaf,error_node};
-//! use std::error::Error;
 //!
 //! #[error_leaf(message = format!("My error"))]
 //! struct MyError {}
@@ -70,7 +69,6 @@
 //! This snippet is equivalent to:
 //! ```
 //! use hierrorchy::error_leaf;
-//! use std::error::Error;
 //!
 //! #[error_leaf(message = format!("My error"))]
 //! struct MyError {}
@@ -82,7 +80,9 @@
 //!
 //! impl std::fmt::Display for MyErrorNode {
 //!     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-//!         write!(f, "my error node: {}", &self.source().expect("MyErrorNode always has a source"))
+//!         match self {
+//!             Self::Variant0(e) => write!(f, "my error node: {}", e),
+//!         }
 //!     }
 //! }
 //!
@@ -152,88 +152,111 @@
 //! error_node! { type MyErrorNode<MyFirstErrorLeaf, MySecondErrorLeaf> = "error node" }
 //! ```
 #![deny(missing_docs)]
-mod error_leaf;
-mod error_node;
-
-use proc_macro::TokenStream;
-use syn::{parse_macro_input, ItemStruct};
+mod assert;
+mod cached_source;
+mod chain;
+mod coded;
+mod json;
+mod message_error;
+mod retryable;
+mod severity;
 
-use crate::{
-    error_leaf::{ErrorLeaf, ErrorLeafConfig},
-    error_node::ErrorNode,
-};
+#[doc(hidden)]
+pub use assert::ReturnsResult;
+#[doc(hidden)]
+pub use cached_source::CachedSource;
+pub use chain::{sources, write_chain, write_numbered_chain, Sources};
+pub use coded::Coded;
+pub use json::ToJson;
+pub use message_error::MessageError;
+pub use retryable::Retryable;
+pub use severity::{HasSeverity, Severity};
+pub use hierrorchy_macros::{error_bridge, error_hierarchy, error_leaf, error_node, error_nodes, ErrorNode};
 
-/// Attribute to mark a Struct definition as an error leaf.
-/// Implementation of `Display` and `Error` is created by the macro.
-///
-/// # Examples
-/// The message must be written as a format macro call.
-///
-/// The scope of the format macro is inside the struct, so internal fields can be accessed with
-/// `self`.
-/// ```
-/// use hierrorchy::error_leaf;
-///
-/// // Format macro form
-/// #[error_leaf(message = format!("{} is wrong", self.myfield))]
-/// struct MyError {
-///    myfield: String,
-/// }
-/// ```
-///
-/// # Arguments
-/// This attribute macro can be configured with keywords, with the structure `<keyword> = <value>`,
-/// separated by commas. Trailing commas are accepted.
+/// The separator between the prefix of an error node and the message of its source, used unless the
+/// node is declared with `display(separator = "...")` or `display(raw_prefix)`.
 ///
-/// The available keywords are reported in the table below.
-///
-/// | keyword | Required? | Description |
-/// | --- | --- | --- |
-/// | `message` | Y | The message format to use in the [std::fmt::Display] implementation. |
-/// | `derive_debug` | N | Whether to add the derive macro for [std::fmt::Debug] trait. Defaults to `true`. |
-#[proc_macro_attribute]
-pub fn error_leaf(attr: TokenStream, item: TokenStream) -> TokenStream {
-    let config = parse_macro_input!(attr as ErrorLeafConfig);
-    let struct_def = parse_macro_input!(item as ItemStruct);
-    ErrorLeaf::new(config, struct_def).to_token_stream()
-}
-
-/// Function-like proc macro to construct error nodes.
-/// The body requires the following format:
-/// `type (name)<variants> [= (string)]`
-/// where `name` is the name to give to the error node (an enum), `variants` is a comma-separated list of other
-/// errors (both leaves and nodes), and `string` is an optional string to use rather than the node
-/// name when printing the error node.
+/// A crate can replace it for all of its nodes by defining a `DEFAULT_NODE_SEP` constant at its
+/// root, which the generated code uses instead of this one.
 ///
-/// # Examples:
+/// # Examples
 /// ```
 /// use hierrorchy::{error_leaf, error_node};
-/// use std::error::Error;
-///
-/// #[error_leaf(message = format!("error child 1"))]
-/// pub struct ErrorChild1 {}
-///
-/// error_node! { type MyErrorNode<ErrorChild1> = "custom prefix" }
-/// ```
 ///
-/// ## Variants with paths
-/// > Since version 0.2.0
+/// #[error_leaf(message = format!("disk is full"))]
+/// struct DiskFullError {}
 ///
-/// error_node also accept variants in the form of paths, e.g. `std::io::Error`.
+/// error_node! { type StorageErrorNode<DiskFullError> = "storage" }
 ///
-/// This allows to write:
-/// ```ignore
-/// error_node! { type MyErrorNode<std::io::Error> = "custom message" }
+/// let error: StorageErrorNode = DiskFullError {}.into();
+/// assert_eq!(error.to_string(), format!("storage{}disk is full", hierrorchy::DEFAULT_NODE_SEP));
 /// ```
-/// rather than:
-/// ```ignore
-/// use std::io::Error as IoError;
-/// error_node! { type MyErrorNode<IoError> = "custom message" }
-/// ```
-///
-#[proc_macro]
-pub fn error_node(tokens: TokenStream) -> TokenStream {
-    let input = parse_macro_input!(tokens as ErrorNode);
+pub const DEFAULT_NODE_SEP: &str = ": ";
+
+/// The crates used by the generated code, re-exported so that the crates using the macros do not
+/// need to depend on them, and the macros emitting the code which depends on the features of this
+/// crate.
+#[doc(hidden)]
+pub mod __private {
+    #[cfg(feature = "anyhow")]
+    pub use anyhow;
+    #[cfg(feature = "serde_json")]
+    pub use serde_json;
+
+    /// The defaults which the crates using the macros can replace with their own items.
+    pub mod defaults {
+        pub use crate::DEFAULT_NODE_SEP;
+    }
+
+    /// Emits the given items only with the `anyhow` feature.
+    #[cfg(feature = "anyhow")]
+    #[macro_export]
+    #[doc(hidden)]
+    macro_rules! __with_anyhow {
+        ($($items:tt)*) => { $($items)* };
+    }
+
+    /// Emits the given items only with the `anyhow` feature.
+    #[cfg(not(feature = "anyhow"))]
+    #[macro_export]
+    #[doc(hidden)]
+    macro_rules! __with_anyhow {
+        ($($items:tt)*) => {};
+    }
+
+    /// Emits the given items only with the `serde_json` feature.
+    #[cfg(feature = "serde_json")]
+    #[macro_export]
+    #[doc(hidden)]
+    macro_rules! __with_serde_json {
+        ($($items:tt)*) => { $($items)* };
+    }
+
+    /// Emits the given items only with the `serde_json` feature.
+    #[cfg(not(feature = "serde_json"))]
+    #[macro_export]
+    #[doc(hidden)]
+    macro_rules! __with_serde_json {
+        ($($items:tt)*) => {};
+    }
+
+    /// Emits the given items only with `--cfg hierrorchy_provide`.
+    #[cfg(hierrorchy_provide)]
+    #[macro_export]
+    #[doc(hidden)]
+    macro_rules! __with_provide {
+        ($($items:tt)*) => { $($items)* };
+    }
+
+    /// Emits the given items only with `--cfg hierrorchy_provide`.
+    #[cfg(not(hierrorchy_provide))]
+    #[macro_export]
+    #[doc(hidden)]
+    macro_rules! __with_provide {
+        ($($items:tt)*) => {};
+    }
 
-    input.to_token_stream()
+    pub use __with_anyhow as with_anyhow;
+    pub use __with_serde_json as with_serde_json;
+    pub use __with_provide as with_provide;
 }
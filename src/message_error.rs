@@ -0,0 +1,40 @@
+use std::{error::Error, fmt};
+
+/// An error which only carries a message.
+///
+/// It is used by the leaves declared with `clone_via_string` to replace their boxed errors when
+/// cloned, keeping the message of the original error but not its type.
+///
+/// # Examples
+/// ```
+/// use hierrorchy::MessageError;
+///
+/// let error = MessageError::new("connection reset");
+/// assert_eq!(error.to_string(), "connection reset");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MessageError {
+    message: String,
+}
+
+impl MessageError {
+    /// Creates an error with the given message.
+    pub fn new(message: impl Into<String>) -> Self {
+        MessageError {
+            message: message.into(),
+        }
+    }
+
+    /// Returns the message of the error.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for MessageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl Error for MessageError {}
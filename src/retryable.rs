@@ -0,0 +1,23 @@
+/// Implemented by the errors which know whether the operation failing with them can be retried.
+///
+/// It is implemented by the leaves declared with `retryable`, returning `true`, and by the error
+/// nodes which are not `#[erased]`, returning the value of their wrapped error. The nodes consider
+/// the wrapped errors which do not implement this trait as not retryable.
+///
+/// # Examples
+/// ```
+/// use hierrorchy::{error_leaf, error_node, Retryable};
+/// use std::error::Error;
+///
+/// #[error_leaf(message = format!("timeout"), retryable)]
+/// struct TimeoutError {}
+///
+/// error_node! { type NetworkErrorNode<TimeoutError, std::io::Error> = "network" }
+///
+/// let error: NetworkErrorNode = TimeoutError {}.into();
+/// assert!(Retryable::is_retryable(&error));
+/// ```
+pub trait Retryable {
+    /// Returns whether the operation which failed with this error can be retried.
+    fn is_retryable(&self) -> bool;
+}
@@ -0,0 +1,48 @@
+/// The severity of an error, used to route errors to different log levels.
+///
+/// Severities are ordered from the least to the most severe, so errors can be filtered with
+/// comparisons. The default severity is [Severity::Error].
+///
+/// # Examples
+/// ```
+/// use hierrorchy::Severity;
+///
+/// assert!(Severity::Fatal > Severity::Error);
+/// assert_eq!(Severity::default(), Severity::Error);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Severity {
+    /// An error which does not prevent the operation from completing.
+    Warn,
+    /// An error which makes the operation fail.
+    #[default]
+    Error,
+    /// An error which the application cannot recover from.
+    Fatal,
+}
+
+/// Implemented by the errors which report a [Severity].
+///
+/// It is implemented by every leaf, returning the `severity` it is declared with or
+/// [Severity::Error] without one, and by the error nodes declared with `#[severity]`, returning the
+/// severity of their wrapped error. The nodes consider the wrapped errors which do not implement
+/// this trait as having the [Severity::Error] severity.
+///
+/// # Examples
+/// ```
+/// use hierrorchy::{error_leaf, error_node, HasSeverity, Severity};
+///
+/// #[error_leaf(message = format!("cache miss"), severity = Warn)]
+/// struct CacheMissError {}
+///
+/// error_node! { #[severity] type StorageErrorNode<CacheMissError, std::io::Error> = "storage" }
+///
+/// let error: StorageErrorNode = CacheMissError {}.into();
+/// assert_eq!(HasSeverity::severity(&error), Severity::Warn);
+/// let error: StorageErrorNode = std::io::Error::other("disk is full").into();
+/// assert_eq!(HasSeverity::severity(&error), Severity::Error);
+/// ```
+pub trait HasSeverity {
+    /// Returns the severity of this error.
+    fn severity(&self) -> Severity;
+}
@@ -0,0 +1,17 @@
+#![cfg(feature = "anyhow")]
+
+use hierrorchy::{error_leaf, error_node};
+
+#[error_leaf(message = format!("disk is full"))]
+struct DiskFullError {}
+
+error_node! { type StorageErrorNode<DiskFullError> = "storage" }
+
+#[test]
+fn context_anyhow_chain_has_context_and_node() {
+    let error: StorageErrorNode = DiskFullError {}.into();
+    let error = error.context_anyhow("saving the report");
+    let chain: Vec<String> = error.chain().map(|it| it.to_string()).collect();
+    assert_eq!(chain, vec!["saving the report", "storage: disk is full", "disk is full"]);
+    assert!(error.downcast_ref::<StorageErrorNode>().is_some());
+}
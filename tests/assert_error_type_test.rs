@@ -0,0 +1,28 @@
+use hierrorchy::{assert_error_type, error_leaf, error_node};
+
+#[error_leaf(message = format!("user {} not found", self.name))]
+struct UserNotFoundError {
+    name: String,
+}
+
+error_node! { type LookupErrorNode<UserNotFoundError> = "lookup failed" }
+
+fn find_user(name: &str) -> Result<u32, LookupErrorNode> {
+    Err(UserNotFoundError { name: name.to_string() }.into())
+}
+
+fn find_user_in(directory: &[&str], name: &str, case_sensitive: bool) -> Result<usize, LookupErrorNode> {
+    directory
+        .iter()
+        .position(|it| if case_sensitive { *it == name } else { it.eq_ignore_ascii_case(name) })
+        .ok_or_else(|| UserNotFoundError { name: name.to_string() }.into())
+}
+
+assert_error_type!(find_user, LookupErrorNode);
+assert_error_type!(find_user_in, LookupErrorNode);
+
+#[test]
+fn asserted_functions_are_callable() {
+    assert_eq!(find_user("ada").unwrap_err().to_string(), "lookup failed: user ada not found");
+    assert_eq!(find_user_in(&["ada", "bob"], "BOB", false).unwrap(), 1);
+}
@@ -0,0 +1,70 @@
+use std::{error::Error, fmt};
+
+use hierrorchy::{error_leaf, write_chain, write_numbered_chain};
+
+#[error_leaf(message = format!("connection reset"))]
+struct ConnectionResetError {}
+
+#[derive(Debug)]
+struct RequestError {
+    source: ConnectionResetError,
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "request failed")
+    }
+}
+
+impl Error for RequestError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+struct ChainDisplay<'a> {
+    error: &'a dyn Error,
+    separator: &'a str,
+}
+
+impl fmt::Display for ChainDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_chain(f, self.error, self.separator)
+    }
+}
+
+#[test]
+fn write_chain_with_sources() {
+    let error = RequestError { source: ConnectionResetError {} };
+    let display = ChainDisplay { error: &error, separator: ": " };
+    assert_eq!(display.to_string(), "request failed: connection reset");
+}
+
+#[test]
+fn write_chain_without_sources() {
+    let error = ConnectionResetError {};
+    let display = ChainDisplay { error: &error, separator: " | " };
+    assert_eq!(display.to_string(), "connection reset");
+}
+
+struct NumberedDisplay<'a> {
+    error: &'a (dyn Error + 'static),
+}
+
+impl fmt::Display for NumberedDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_numbered_chain(f, self.error, 1)
+    }
+}
+
+#[test]
+fn write_numbered_chain_single_level() {
+    let error = ConnectionResetError {};
+    assert_eq!(NumberedDisplay { error: &error }.to_string(), "[1] connection reset");
+}
+
+#[test]
+fn write_numbered_chain_with_foreign_source() {
+    let error = RequestError { source: ConnectionResetError {} };
+    assert_eq!(NumberedDisplay { error: &error }.to_string(), "[1] request failed: [2] connection reset");
+}
@@ -1,4 +1,6 @@
-use std::{error::Error, fmt::Debug, io, marker::PhantomData};
+#![allow(dead_code)]
+
+use std::{fmt::Debug, io, marker::PhantomData};
 use hierrorchy::{error_leaf, error_node};
 
 error_node! {
@@ -24,7 +26,7 @@ impl<T> Debug for GenericErrorWithoutDebug<T> {
     }
 }
 
-#[error_leaf(message = println!(""))]
+#[error_leaf(message = format!(""))]
 struct MyError {}
 
 error_node! {
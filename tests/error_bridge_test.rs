@@ -0,0 +1,52 @@
+use hierrorchy::{error_bridge, error_leaf, error_node};
+
+#[error_leaf(message = format!("invalid header"))]
+struct InvalidHeaderError {}
+
+#[error_leaf(message = format!("invalid body"))]
+struct InvalidBodyError {}
+
+#[error_leaf(message = format!("connection lost"))]
+struct ConnectionLostError {}
+
+error_node! { type ProtocolErrorNode<InvalidHeaderError, InvalidBodyError> = "protocol" }
+
+error_node! { type ClientErrorNode<ConnectionLostError, InvalidBodyError, InvalidHeaderError> = "client" }
+
+error_bridge! { from ProtocolErrorNode(InvalidHeaderError, InvalidBodyError) into ClientErrorNode }
+
+fn decode() -> Result<(), ProtocolErrorNode> {
+    Err(InvalidBodyError {})?
+}
+
+fn request() -> Result<(), ClientErrorNode> {
+    decode()?;
+    Ok(())
+}
+
+#[test]
+fn bridge_rewraps_each_variant() {
+    let error: ClientErrorNode = ProtocolErrorNode::from(InvalidHeaderError {}).into();
+    assert!(matches!(error, ClientErrorNode::Variant2(_)));
+    assert_eq!(error.to_string(), "client: invalid header");
+    let error = request().unwrap_err();
+    assert!(matches!(error, ClientErrorNode::Variant1(_)));
+    assert_eq!(error.to_string(), "client: invalid body");
+}
+
+hierrorchy::error_hierarchy! {
+    TransportError = "transport" { TimeoutError("timed out"), Io(std::io::Error) }
+}
+
+error_node! { type FetchErrorNode<Timeout = TimeoutError, std::io::Error, ConnectionLostError> = "fetch" }
+
+error_bridge! { from TransportError(TimeoutError = TimeoutError, Io = std::io::Error) into FetchErrorNode }
+
+#[test]
+fn bridge_matches_named_variants() {
+    let error: FetchErrorNode = TransportError::from(TimeoutError {}).into();
+    assert!(matches!(error, FetchErrorNode::Timeout(_)));
+    assert_eq!(error.to_string(), "fetch: timed out");
+    let error: FetchErrorNode = TransportError::from(std::io::Error::other("broken pipe")).into();
+    assert!(matches!(error, FetchErrorNode::Variant1(_)));
+}
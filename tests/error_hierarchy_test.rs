@@ -0,0 +1,48 @@
+use std::error::Error;
+
+use hierrorchy::{error_hierarchy, error_leaf};
+
+#[error_leaf(message = format!("unexpected end of file"))]
+struct EofError {}
+
+error_hierarchy! {
+    ToolError = "tool" {
+        #[result]
+        ParseError = "parse" { BadTokenError("bad token"), EofError },
+        Io(std::io::Error),
+        std::fmt::Error,
+    }
+}
+
+fn parse(eof: bool) -> ParseErrorResult<()> {
+    if eof {
+        Err(EofError {})?
+    }
+    Err(BadTokenError {})?
+}
+
+#[test]
+fn hierarchy_generates_leaves_and_nodes() {
+    let error: ToolError = parse(false).unwrap_err().into();
+    assert!(matches!(error, ToolError::ParseError(ParseError::BadTokenError(_))));
+    assert_eq!(error.to_string(), "tool: parse: bad token");
+    let error: ToolError = parse(true).unwrap_err().into();
+    assert!(matches!(error, ToolError::ParseError(ParseError::EofError(_))));
+    let error: ToolError = std::io::Error::other("broken pipe").into();
+    assert!(matches!(error, ToolError::Io(_)));
+    assert!(error.source().unwrap().is::<std::io::Error>());
+    let error: ToolError = std::fmt::Error.into();
+    assert!(matches!(error, ToolError::Error(_)));
+}
+
+mod shell {
+    hierrorchy::error_hierarchy! {
+        pub(crate) ShellError = "shell" { UnknownCommandError("unknown command") }
+    }
+}
+
+#[test]
+fn hierarchy_keeps_restricted_visibility() {
+    let error: shell::ShellError = shell::UnknownCommandError {}.into();
+    assert_eq!(error.to_string(), "shell: unknown command");
+}
@@ -0,0 +1,656 @@
+use std::{collections::HashSet, error::Error};
+
+use hierrorchy::error_leaf;
+
+#[error_leaf(message = format!("request {} failed: {:?}", self.id, self.reason), typed_builder)]
+struct RequestError {
+    id: u32,
+    reason: Option<String>,
+    retries: Option<u8>,
+}
+
+#[test]
+fn builder_with_partial_fields() {
+    let error = RequestError::builder().id(7).build().unwrap();
+    assert_eq!(error.id, 7);
+    assert_eq!(error.reason, None);
+    assert_eq!(error.retries, None);
+    assert_eq!(error.to_string(), "request 7 failed: None");
+}
+
+#[test]
+fn builder_with_all_fields() {
+    let error = RequestError::builder()
+        .id(3)
+        .reason(Some(String::from("timeout")))
+        .retries(Some(2))
+        .build()
+        .unwrap();
+    assert_eq!(error.reason.as_deref(), Some("timeout"));
+    assert_eq!(error.retries, Some(2));
+}
+
+#[test]
+fn builder_missing_required_field() {
+    let error = RequestError::builder().retries(Some(1)).build().unwrap_err();
+    assert_eq!(error.to_string(), "the required field 'id' is not set");
+}
+
+mod with_result_alias {
+    use hierrorchy::error_leaf;
+
+    #[allow(dead_code)]
+    type Result<T> = std::result::Result<T, String>;
+
+    #[error_leaf(message = format!("lease {} expired", self.lease), typed_builder)]
+    pub struct LeaseExpiredError {
+        pub lease: u64,
+    }
+
+    #[error_leaf(self_message)]
+    pub struct LeaseRevokedError(pub String);
+}
+
+#[test]
+fn builder_under_result_alias() {
+    let error = with_result_alias::LeaseExpiredError::builder().lease(4).build().unwrap();
+    assert_eq!(error.to_string(), "lease 4 expired");
+}
+
+#[test]
+fn self_message_from_str_under_result_alias() {
+    let error: with_result_alias::LeaseRevokedError = "lease revoked".parse().unwrap();
+    assert_eq!(error.0, "lease revoked");
+}
+
+#[error_leaf(message = format!("file {} not found", self.path), hashable)]
+struct FileNotFoundError {
+    path: String,
+}
+
+#[test]
+fn hashable_leaves_in_hash_set() {
+    let mut errors = HashSet::new();
+    errors.insert(FileNotFoundError { path: String::from("a.txt") });
+    errors.insert(FileNotFoundError { path: String::from("b.txt") });
+    errors.insert(FileNotFoundError { path: String::from("a.txt") });
+    assert_eq!(errors.len(), 2);
+    assert!(errors.contains(&FileNotFoundError { path: String::from("b.txt") }));
+}
+
+#[error_leaf(message = format!("enabled conditional derive"))]
+#[cfg_attr(all(), derive(Clone, PartialEq))]
+struct EnabledCfgAttrError {
+    code: u8,
+}
+
+#[error_leaf(message = format!("disabled conditional derive"))]
+#[cfg_attr(any(), derive(Clone, PartialEq))]
+struct DisabledCfgAttrError {
+    code: u8,
+}
+
+#[test]
+fn cfg_attr_is_preserved() {
+    let error = EnabledCfgAttrError { code: 1 };
+    assert!(error.clone() == error);
+    assert_eq!(DisabledCfgAttrError { code: 2 }.code, 2);
+}
+
+#[error_leaf(message = format!("pending tasks {:?} failed: {}", self.tasks, self.details.join(", ")))]
+struct PendingTasksError {
+    tasks: Vec<String>,
+    details: Box<[String]>,
+}
+
+#[test]
+fn message_borrows_non_copy_fields() {
+    let error = PendingTasksError {
+        tasks: vec![String::from("fetch"), String::from("parse")],
+        details: vec![String::from("timeout"), String::from("bad input")].into_boxed_slice(),
+    };
+    assert_eq!(error.to_string(), "pending tasks [\"fetch\", \"parse\"] failed: timeout, bad input");
+    // Displaying the error does not consume nor modify it.
+    assert_eq!(error.to_string(), "pending tasks [\"fetch\", \"parse\"] failed: timeout, bad input");
+    assert_eq!(error.tasks.len(), 2);
+}
+
+#[derive(Debug, PartialEq)]
+enum ConfigErrorKind {
+    Missing,
+    Invalid,
+}
+
+#[error_leaf(message = format!("configuration error: {:?}", self.kind), expose_kind = kind)]
+struct ConfigError {
+    kind: ConfigErrorKind,
+}
+
+#[test]
+fn expose_kind_accessor() {
+    let error = ConfigError { kind: ConfigErrorKind::Invalid };
+    assert_eq!(error.kind(), &ConfigErrorKind::Invalid);
+    let error = ConfigError { kind: ConfigErrorKind::Missing };
+    assert_eq!(error.kind(), &ConfigErrorKind::Missing);
+}
+
+#[error_leaf(message = format!("disk is full"), kind_str = "disk_full")]
+struct DiskFullError {}
+
+#[test]
+fn kind_str_on_leaf() {
+    assert_eq!(DiskFullError::KIND, "disk_full");
+    assert_eq!(DiskFullError {}.kind_str(), "disk_full");
+}
+
+#[error_leaf(message = format!("query {} failed after {} ms", self.query, self.elapsed_ms), precompute)]
+struct SlowQueryError {
+    query: String,
+    elapsed_ms: u64,
+}
+
+#[test]
+fn precomputed_message_matches_formatted_one() {
+    let error = SlowQueryError::new(String::from("SELECT 1"), 1200);
+    let expected = format!("query {} failed after {} ms", error.query, error.elapsed_ms);
+    assert_eq!(error.precomputed_message, expected);
+    assert_eq!(error.to_string(), expected);
+}
+
+#[error_leaf(message = format!("job {} failed", self.id), precompute, typed_builder)]
+struct JobError {
+    id: u32,
+}
+
+#[test]
+fn builder_uses_precomputing_constructor() {
+    let error = JobError::builder().id(5).build().unwrap();
+    assert_eq!(error.to_string(), "job 5 failed");
+}
+
+#[error_leaf]
+enum ParseError {
+    #[msg("unexpected end of input")]
+    UnexpectedEnd,
+    #[msg("unexpected token '{_0}'")]
+    UnexpectedToken(String),
+    #[msg("line {line} is longer than {max} characters")]
+    LineTooLong { line: usize, max: usize },
+}
+
+#[test]
+fn enum_leaf_messages() {
+    use std::error::Error;
+    assert_eq!(ParseError::UnexpectedEnd.to_string(), "unexpected end of input");
+    assert_eq!(ParseError::UnexpectedToken(String::from("}")).to_string(), "unexpected token '}'");
+    let error = ParseError::LineTooLong { line: 4, max: 80 };
+    assert_eq!(error.to_string(), "line 4 is longer than 80 characters");
+    assert!(error.source().is_none());
+}
+
+const STORAGE_PREFIX: &str = "storage";
+const QUOTA_MESSAGE: &str = "storage: quota exceeded";
+
+#[error_leaf(message = QUOTA_MESSAGE)]
+struct QuotaExceededError {}
+
+#[error_leaf(message = concat!("storage", ": read-only volume"))]
+struct ReadOnlyVolumeError {}
+
+#[test]
+fn const_messages() {
+    assert_eq!(QuotaExceededError {}.to_string(), format!("{}: quota exceeded", STORAGE_PREFIX));
+    assert_eq!(ReadOnlyVolumeError {}.to_string(), "storage: read-only volume");
+}
+
+#[error_leaf(message = format!("upload of {} failed: {}", self.file, self.cause), clone_via_string)]
+struct UploadError {
+    file: String,
+    cause: Box<dyn std::error::Error + Send + Sync>,
+}
+
+#[test]
+fn clone_via_string_keeps_messages() {
+    let error = UploadError {
+        file: String::from("report.pdf"),
+        cause: Box::new(std::io::Error::new(std::io::ErrorKind::BrokenPipe, "pipe closed")),
+    };
+    let cloned = error.clone();
+    assert_eq!(cloned.to_string(), error.to_string());
+    assert_eq!(cloned.file, "report.pdf");
+    assert!(cloned.cause.is::<hierrorchy::MessageError>());
+    assert_eq!(cloned.cause.to_string(), "pipe closed");
+}
+
+#[error_leaf("quota of {user} exceeded by {excess:.1} MB", code = 413)]
+struct StorageQuotaError {
+    user: String,
+    excess: f64,
+}
+
+#[test]
+fn interpolated_message_refers_to_fields() {
+    let error = StorageQuotaError { user: String::from("alice"), excess: 2.25 };
+    assert_eq!(error.to_string(), "quota of alice exceeded by 2.2 MB");
+    assert_eq!(error.as_parts().0, 413);
+}
+
+#[error_leaf]
+#[error("cannot open {path}: permission denied for user {user}")]
+struct PermissionDeniedError {
+    path: String,
+    user: String,
+}
+
+#[test]
+fn thiserror_compat_message() {
+    let error = PermissionDeniedError {
+        path: String::from("/etc/shadow"),
+        user: String::from("guest"),
+    };
+    assert_eq!(error.to_string(), "cannot open /etc/shadow: permission denied for user guest");
+}
+
+#[error_leaf]
+#[error("bad flag {f}")]
+struct FlagError {
+    f: char,
+}
+
+#[test]
+fn thiserror_compat_field_named_like_formatter() {
+    assert_eq!(FlagError { f: 'x' }.to_string(), "bad flag x");
+}
+
+#[error_leaf(message = format!("rate limit of {} requests exceeded", self.limit), kind_str = "rate_limited", log_line)]
+struct RateLimitedError {
+    limit: u32,
+}
+
+#[error_leaf(message = format!("unknown failure"), log_line)]
+struct UnknownFailureError {}
+
+#[test]
+fn leaf_log_lines() {
+    assert_eq!(
+        RateLimitedError { limit: 100 }.to_log_line(),
+        "rate_limited||rate limit of 100 requests exceeded"
+    );
+    assert_eq!(UnknownFailureError {}.to_log_line(), "||unknown failure");
+}
+
+#[error_leaf(self_message)]
+struct RemoteError {
+    message: String,
+}
+
+#[error_leaf(self_message)]
+struct RemoteTupleError(String);
+
+#[test]
+fn self_message_leaf_from_str() {
+    let error: RemoteError = "remote host closed the connection".parse().unwrap();
+    assert_eq!(error.to_string(), "remote host closed the connection");
+    assert_eq!(error.message, "remote host closed the connection");
+    let error = RemoteTupleError::from(String::from("checksum mismatch"));
+    assert_eq!(error.to_string(), "checksum mismatch");
+    assert_eq!(RemoteTupleError::from("timeout").0, "timeout");
+}
+
+#[error_leaf(message = format!("invalid limit {}", self.limit))]
+struct InvalidLimitError {
+    limit: String,
+    #[source]
+    cause: std::num::ParseIntError,
+}
+
+#[error_leaf(message = format!("invalid offset"), source = cause)]
+struct InvalidOffsetError {
+    cause: std::num::ParseIntError,
+}
+
+#[test]
+fn source_field_is_returned_as_source() {
+    let cause = "-1".parse::<u32>().unwrap_err();
+    let error = InvalidLimitError { limit: String::from("-1"), cause: cause.clone() };
+    assert_eq!(error.source().unwrap().to_string(), cause.to_string());
+    assert_eq!(error.limit, "-1");
+    let error = InvalidOffsetError { cause };
+    assert!(error.source().unwrap().is::<std::num::ParseIntError>());
+}
+
+#[error_leaf(message = format!("cannot load plugin {}", self.name))]
+struct PluginLoadError {
+    name: String,
+    #[source]
+    cause: Box<dyn Error + Send + Sync>,
+}
+
+#[error_leaf(transparent)]
+struct BoxedForeignError(Box<dyn Error>);
+
+#[test]
+fn boxed_source_field_is_unboxed() {
+    let error = PluginLoadError { name: String::from("audio"), cause: Box::new(std::fmt::Error) };
+    assert!(error.source().unwrap().is::<std::fmt::Error>());
+    let error = BoxedForeignError(Box::new(std::fmt::Error));
+    assert!(error.source().unwrap().is::<std::fmt::Error>());
+    assert_eq!(error.to_string(), std::fmt::Error.to_string());
+}
+
+#[error_leaf(transparent)]
+struct ForeignError {
+    inner: std::num::ParseIntError,
+}
+
+#[test]
+fn transparent_leaf_forwards_to_inner_error() {
+    let error = ForeignError { inner: "x".parse::<u8>().unwrap_err() };
+    assert_eq!(error.to_string(), error.inner.to_string());
+    assert!(error.source().unwrap().is::<std::num::ParseIntError>());
+}
+
+#[error_leaf(message = format!("cache miss"), severity = Warn)]
+struct CacheMissError {}
+
+#[error_leaf(message = format!("corrupted index"), severity = Fatal)]
+struct CorruptedIndexError {}
+
+#[test]
+fn leaf_severity() {
+    use hierrorchy::Severity;
+    assert_eq!(CacheMissError {}.severity(), Severity::Warn);
+    assert!(!CacheMissError {}.is_at_least(Severity::Error));
+    assert_eq!(CorruptedIndexError {}.severity(), Severity::Fatal);
+    assert!(CorruptedIndexError {}.is_at_least(Severity::Error));
+}
+
+#[error_leaf(message = format!("index rebuilt"))]
+struct IndexRebuiltError {}
+
+#[test]
+fn leaf_severity_defaults_to_error() {
+    use hierrorchy::{HasSeverity, Severity};
+    assert_eq!(IndexRebuiltError {}.severity(), Severity::Error);
+    assert_eq!(HasSeverity::severity(&IndexRebuiltError {}), Severity::Error);
+}
+
+#[error_leaf(message = format!("exit code {}: {}", self.code, Self::describe(self.code)))]
+struct ExitCodeError {
+    code: i32,
+}
+
+impl ExitCodeError {
+    fn describe(code: i32) -> &'static str {
+        match code {
+            1 => "general error",
+            2 => "misuse of shell builtin",
+            _ => "unknown",
+        }
+    }
+}
+
+#[error_leaf(message = format!("signal {}: {}", self.signal, Self::describe(self.signal)), precompute)]
+struct SignalError {
+    signal: u8,
+}
+
+impl SignalError {
+    fn describe(signal: u8) -> String {
+        format!("terminated by SIG{}", if signal == 9 { "KILL" } else { "TERM" })
+    }
+}
+
+#[error_leaf(message = Self::MESSAGE)]
+struct DeprecatedApiError {}
+
+impl DeprecatedApiError {
+    const MESSAGE: &'static str = "the API is deprecated";
+}
+
+#[test]
+fn messages_with_associated_items() {
+    assert_eq!(ExitCodeError { code: 2 }.to_string(), "exit code 2: misuse of shell builtin");
+    assert_eq!(SignalError::new(9).to_string(), "signal 9: terminated by SIGKILL");
+    assert_eq!(DeprecatedApiError {}.to_string(), "the API is deprecated");
+}
+
+#[error_leaf(message = format!("missing column '{}' in table {}", self.column, self.table))]
+#[non_exhaustive]
+struct MissingColumnError {
+    table: String,
+    column: String,
+}
+
+#[error_leaf(message = format!("row {} is locked", self.0))]
+#[non_exhaustive]
+struct LockedRowError(u64);
+
+#[test]
+fn non_exhaustive_leaf_constructor() {
+    let error = MissingColumnError::new(String::from("users"), String::from("email"));
+    assert_eq!(error.to_string(), "missing column 'email' in table users");
+    assert_eq!(error.table, "users");
+    assert_eq!(LockedRowError::new(42).to_string(), "row 42 is locked");
+}
+
+#[error_leaf(message = format!("{SELF_TYPE}: checksum {} does not match", self.checksum))]
+struct ChecksumMismatchError {
+    checksum: u32,
+}
+
+#[error_leaf(message = format!("{SELF_TYPE} at {}", self.offset), precompute)]
+struct TruncatedFrameError {
+    offset: usize,
+}
+
+#[test]
+fn message_with_self_type() {
+    assert_eq!(
+        ChecksumMismatchError { checksum: 7 }.to_string(),
+        "ChecksumMismatchError: checksum 7 does not match"
+    );
+    assert_eq!(TruncatedFrameError::new(12).to_string(), "TruncatedFrameError at 12");
+}
+
+#[error_leaf(message = format!("replication to {} replicas failed", self.failures.len()), source_fn = self.first_failure())]
+struct ReplicationError {
+    failures: Vec<ChecksumMismatchError>,
+}
+
+impl ReplicationError {
+    fn first_failure(&self) -> Option<&(dyn Error + 'static)> {
+        self.failures.first().map(|it| it as &(dyn Error + 'static))
+    }
+}
+
+#[test]
+fn leaf_source_from_method() {
+    let error = ReplicationError {
+        failures: vec![ChecksumMismatchError { checksum: 3 }, ChecksumMismatchError { checksum: 4 }],
+    };
+    assert_eq!(
+        error.source().unwrap().to_string(),
+        "ChecksumMismatchError: checksum 3 does not match"
+    );
+    assert!(ReplicationError { failures: vec![] }.source().is_none());
+}
+
+#[error_leaf(message = format!("read of {} failed", self.path), into_io = NotFound)]
+struct MissingFileError {
+    path: String,
+}
+
+fn read_config() -> std::io::Result<String> {
+    Err(MissingFileError { path: String::from("/etc/app.toml") })?
+}
+
+#[test]
+fn leaf_into_io_error() {
+    let error = read_config().unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+    assert_eq!(error.to_string(), "read of /etc/app.toml failed");
+}
+
+#[error_leaf(message = format!("unexpected value {:?}", self.value), typed_builder)]
+struct UnexpectedValueError<T: std::fmt::Debug> {
+    value: Option<T>,
+}
+
+#[test]
+fn generic_leaf_keeps_generics() {
+    let error = UnexpectedValueError::<u8>::builder().value(Some(3)).build().unwrap();
+    assert_eq!(error.to_string(), "unexpected value Some(3)");
+    let error: &dyn Error = &UnexpectedValueError { value: Some("text") };
+    assert_eq!(error.to_string(), "unexpected value Some(\"text\")");
+}
+
+#[error_leaf(message = format!("{} is out of range", self.value), precompute, code = 416)]
+struct OutOfRangeError<T>
+where
+    T: std::fmt::Display + std::fmt::Debug,
+{
+    value: T,
+}
+
+#[test]
+fn generic_leaf_keeps_where_clause() {
+    let error = OutOfRangeError::new(1.5);
+    assert_eq!(error.to_string(), "1.5 is out of range");
+    assert_eq!(error.as_parts(), (416, String::from("1.5 is out of range")));
+}
+
+#[error_leaf(message = format!("access to {} denied", self.resource), timestamp, precompute)]
+struct AccessDeniedError {
+    resource: String,
+}
+
+#[test]
+fn timestamped_leaf_records_creation_time() {
+    let before = std::time::SystemTime::now();
+    let error = AccessDeniedError::new(String::from("/admin"));
+    let elapsed = error.created_at().duration_since(before).unwrap();
+    assert!(elapsed < std::time::Duration::from_secs(5));
+    assert_eq!(error.to_string(), "access to /admin denied");
+}
+
+/// A handle which cannot be cloned.
+#[derive(Debug, Default)]
+struct SocketHandle {
+    port: u16,
+}
+
+#[error_leaf(message = format!("socket on port {} closed", self.socket.port), precompute, clone_message_only)]
+struct SocketClosedError {
+    socket: SocketHandle,
+}
+
+#[test]
+fn clone_message_only_keeps_message() {
+    let error = SocketClosedError::new(SocketHandle { port: 8080 });
+    let cloned = error.clone();
+    assert_eq!(cloned.to_string(), "socket on port 8080 closed");
+    assert_eq!(cloned.socket.port, 0);
+    assert_eq!(error.socket.port, 8080);
+}
+
+#[error_leaf(plural(self.count, "{} file failed", "{} files failed"))]
+struct FailedFilesError {
+    count: usize,
+}
+
+#[error_leaf(plural(self.count, "a file failed", "{} files failed"), precompute)]
+struct PrecomputedFailedFilesError {
+    count: u32,
+}
+
+#[test]
+fn plural_message_depends_on_count() {
+    assert_eq!(FailedFilesError { count: 0 }.to_string(), "0 files failed");
+    assert_eq!(FailedFilesError { count: 1 }.to_string(), "1 file failed");
+    assert_eq!(FailedFilesError { count: 42 }.to_string(), "42 files failed");
+    assert_eq!(PrecomputedFailedFilesError::new(1).to_string(), "a file failed");
+    assert_eq!(PrecomputedFailedFilesError::new(2).to_string(), "2 files failed");
+}
+
+#[error_leaf(fmt = |this, f| {
+    if this.retries == 0 {
+        return write!(f, "connection to {} failed", this.host);
+    }
+    write!(f, "connection to {} failed", this.host)?;
+    write!(f, " after {} retries", this.retries)
+})]
+struct ConnectionFailedError {
+    host: String,
+    retries: u8,
+}
+
+#[error_leaf(fmt = |this, f| write!(f, "{} bytes lost", this.count), precompute)]
+struct BytesLostError {
+    count: usize,
+}
+
+#[test]
+fn custom_fmt_writes_message() {
+    let error = ConnectionFailedError { host: String::from("db"), retries: 0 };
+    assert_eq!(error.to_string(), "connection to db failed");
+    let error = ConnectionFailedError { host: String::from("db"), retries: 3 };
+    assert_eq!(error.to_string(), "connection to db failed after 3 retries");
+    assert_eq!(BytesLostError::new(12).to_string(), "12 bytes lost");
+}
+
+#[error_leaf(message = format!("request timed out"), retryable)]
+struct RequestTimeoutError {}
+
+#[test]
+fn retryable_leaf() {
+    assert!(RequestTimeoutError {}.is_retryable());
+    assert!(hierrorchy::Retryable::is_retryable(&RequestTimeoutError {}));
+}
+
+#[error_leaf(message = format!("resource {} not found", self.id), code = 404, log_line)]
+struct ResourceNotFoundError {
+    id: u32,
+}
+
+#[test]
+fn coded_leaf_parts() {
+    let error = ResourceNotFoundError { id: 7 };
+    assert_eq!(ResourceNotFoundError::CODE, 404);
+    assert_eq!(error.code(), 404);
+    assert_eq!(error.as_parts(), (404, String::from("resource 7 not found")));
+    assert_eq!(error.to_log_line(), "|404|resource 7 not found");
+    assert_eq!(UnknownFailureError {}.as_parts(), (0, String::from("unknown failure")));
+}
+
+#[error_leaf("token of {user} expired", no_debug)]
+struct ExpiredTokenError {
+    user: String,
+    token: String,
+}
+
+impl std::fmt::Debug for ExpiredTokenError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ExpiredTokenError")
+            .field("user", &self.user)
+            .field("token", &"<redacted>")
+            .finish()
+    }
+}
+
+#[error_leaf("unknown user {user}", no_debug)]
+#[derive(Debug)]
+struct UnknownUserError {
+    user: String,
+}
+
+#[test]
+fn no_debug_leaf_keeps_own_debug() {
+    let error = ExpiredTokenError { user: String::from("admin"), token: String::from("secret") };
+    assert_eq!(error.to_string(), "token of admin expired");
+    assert_eq!(format!("{:?}", error), "ExpiredTokenError { user: \"admin\", token: \"<redacted>\" }");
+    assert_eq!(
+        format!("{:?}", UnknownUserError { user: String::from("guest") }),
+        "UnknownUserError { user: \"guest\" }"
+    );
+}
@@ -0,0 +1,52 @@
+use std::error::Error;
+
+use hierrorchy::{error_leaf, error_node, ErrorNode};
+
+#[error_leaf(message = format!("invalid syntax"))]
+struct SyntaxError {}
+
+#[error_leaf(message = format!("missing key"))]
+struct MissingKeyError {}
+
+error_node! { type LookupErrorNode<MissingKeyError> = "lookup" }
+
+#[derive(Debug, ErrorNode)]
+#[error_node(prefix = "config")]
+enum ConfigError {
+    Syntax(SyntaxError),
+    Lookup { node: LookupErrorNode },
+    Read { path: String, #[source] cause: std::io::Error },
+    Decode(usize, #[source] std::fmt::Error),
+}
+
+#[derive(Debug, ErrorNode)]
+enum UnprefixedError<E: Error + 'static> {
+    Inner(E),
+}
+
+fn parse() -> Result<(), ConfigError> {
+    Err(SyntaxError {})?
+}
+
+#[test]
+fn derived_node_displays_prefix_and_source() {
+    let error = parse().unwrap_err();
+    assert!(matches!(error, ConfigError::Syntax(_)));
+    assert_eq!(error.to_string(), "config: invalid syntax");
+    let error: ConfigError = LookupErrorNode::from(MissingKeyError {}).into();
+    assert_eq!(error.to_string(), "config: lookup: missing key");
+    assert!(error.source().unwrap().is::<LookupErrorNode>());
+    let error: UnprefixedError<SyntaxError> = SyntaxError {}.into();
+    assert_eq!(error.to_string(), "UnprefixedError: invalid syntax");
+}
+
+#[test]
+fn derived_node_uses_marked_source() {
+    let error = ConfigError::Read { path: String::from("app.toml"), cause: std::io::Error::other("denied") };
+    assert_eq!(error.to_string(), "config: denied");
+    assert!(error.source().unwrap().is::<std::io::Error>());
+    assert!(matches!(&error, ConfigError::Read { path, .. } if path == "app.toml"));
+    let error = ConfigError::Decode(3, std::fmt::Error);
+    assert!(error.source().unwrap().is::<std::fmt::Error>());
+    assert!(matches!(error, ConfigError::Decode(3, _)));
+}
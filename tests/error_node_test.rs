@@ -0,0 +1,1248 @@
+use std::{
+    borrow::Cow,
+    error::Error,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use hierrorchy::{error_leaf, error_node, error_nodes};
+
+#[error_leaf(message = format!("value {} is out of range", self.value))]
+struct OutOfRangeError {
+    value: i32,
+}
+
+error_node! { type ValidationErrorNode<OutOfRangeError> = "validation failed" }
+
+error_node! { type RequestErrorNode<ValidationErrorNode> = "request rejected" }
+
+#[test]
+fn chain_messages_per_level() {
+    let error: RequestErrorNode = ValidationErrorNode::from(OutOfRangeError { value: 12 }).into();
+    assert_eq!(
+        error.chain_messages(),
+        vec![
+            "request rejected: validation failed: value 12 is out of range",
+            "validation failed: value 12 is out of range",
+            "value 12 is out of range",
+        ]
+    );
+}
+
+#[test]
+fn inner_returns_wrapped_error() {
+    let error: ValidationErrorNode = OutOfRangeError { value: -3 }.into();
+    assert_eq!(error.inner().to_string(), "value -3 is out of range");
+    assert!(error.inner().is::<OutOfRangeError>());
+}
+
+#[error_leaf(message = format!("wrapped error: {}", self.inner))]
+struct WrapError<T: Error> {
+    inner: T,
+}
+
+#[error_leaf(message = format!("plain error"))]
+struct PlainError {}
+
+error_node! { type BoundedErrorNode<T: Error + Send><WrapError<T>, PlainError> = "bounded" }
+
+error_node! { type WhereErrorNode<T><WrapError<T>, PlainError> where T: Error + Send = "where" }
+
+#[test]
+fn generic_node_with_inline_bounds() {
+    let error: BoundedErrorNode<OutOfRangeError> = WrapError { inner: OutOfRangeError { value: 3 } }.into();
+    assert_eq!(error.to_string(), "bounded: wrapped error: value 3 is out of range");
+    let error: BoundedErrorNode<OutOfRangeError> = PlainError {}.into();
+    assert!(matches!(error, BoundedErrorNode::Variant1(_)));
+}
+
+#[test]
+fn generic_node_with_where_clause() {
+    let error: WhereErrorNode<OutOfRangeError> = WrapError { inner: OutOfRangeError { value: 4 } }.into();
+    assert_eq!(error.to_string(), "where: wrapped error: value 4 is out of range");
+}
+
+static CACHED_TRANSLATIONS: AtomicUsize = AtomicUsize::new(0);
+static UNCACHED_TRANSLATIONS: AtomicUsize = AtomicUsize::new(0);
+
+fn translate(key: &str, counter: &AtomicUsize) -> String {
+    counter.fetch_add(1, Ordering::SeqCst);
+    format!("translated {}", key)
+}
+
+error_node! {
+    #[display(cache_prefix)]
+    type CachedPrefixErrorNode<PlainError> = translate("cached", &CACHED_TRANSLATIONS)
+}
+
+error_node! { type UncachedPrefixErrorNode<PlainError> = translate("uncached", &UNCACHED_TRANSLATIONS) }
+
+fn translate_cow(key: &'static str) -> Cow<'static, str> {
+    match key {
+        "greeting" => Cow::Owned(format!("{}, {}", "hello", "world")),
+        _ => Cow::Borrowed(key),
+    }
+}
+
+error_node! { type TranslatedErrorNode<PlainError> = translate_cow("greeting") }
+
+error_node! { type UntranslatedErrorNode<PlainError> = translate_cow("untranslated") }
+
+#[test]
+fn cow_prefixes_are_written() {
+    let error: TranslatedErrorNode = PlainError {}.into();
+    assert_eq!(error.to_string(), "hello, world: plain error");
+    let error: UntranslatedErrorNode = PlainError {}.into();
+    assert_eq!(error.to_string(), "untranslated: plain error");
+}
+
+#[test]
+fn cached_prefix_is_computed_once() {
+    let error: CachedPrefixErrorNode = PlainError {}.into();
+    for _ in 0..3 {
+        assert_eq!(error.to_string(), "translated cached: plain error");
+    }
+    assert_eq!(CACHED_TRANSLATIONS.load(Ordering::SeqCst), 1);
+}
+
+#[test]
+fn uncached_prefix_is_computed_each_time() {
+    let error: UncachedPrefixErrorNode = PlainError {}.into();
+    for _ in 0..3 {
+        assert_eq!(error.to_string(), "translated uncached: plain error");
+    }
+    assert_eq!(UNCACHED_TRANSLATIONS.load(Ordering::SeqCst), 3);
+}
+
+trait DomainError: Error {
+    fn code(&self) -> u16;
+}
+
+#[error_leaf(message = format!("quota exceeded"))]
+struct QuotaExceededError {}
+
+impl DomainError for QuotaExceededError {
+    fn code(&self) -> u16 {
+        429
+    }
+}
+
+error_node! { type DomainErrorNode<Box<dyn DomainError>, PlainError> = "domain" }
+
+#[test]
+fn trait_object_variant_is_source() {
+    let error: DomainErrorNode = (Box::new(QuotaExceededError {}) as Box<dyn DomainError>).into();
+    assert_eq!(error.to_string(), "domain: quota exceeded");
+    assert!(error.source().unwrap().is::<QuotaExceededError>());
+    match error {
+        DomainErrorNode::Variant0(domain_error) => assert_eq!(domain_error.code(), 429),
+        DomainErrorNode::Variant1(_) => unreachable!(),
+    }
+}
+
+#[error_leaf(message = format!("second plain error"))]
+struct OtherPlainError {}
+
+error_node! { #[repr(u8)] type TaggedErrorNode<PlainError = 1, OtherPlainError = 2> = "tagged" }
+
+error_node! { #[repr(i16)] type ImplicitTaggedErrorNode<PlainError = 10, OtherPlainError> = "tagged" }
+
+#[test]
+fn explicit_discriminant_tags() {
+    let error: TaggedErrorNode = PlainError {}.into();
+    assert_eq!(error.kind(), TaggedErrorNodeKind::Variant0);
+    assert_eq!(error.tag(), 1u8);
+    let error: TaggedErrorNode = OtherPlainError {}.into();
+    assert_eq!(error.kind(), TaggedErrorNodeKind::Variant1);
+    assert_eq!(error.tag(), 2u8);
+    assert_eq!(TaggedErrorNodeKind::Variant1 as u8, 2);
+}
+
+#[test]
+fn is_kind_predicate() {
+    let error: TaggedErrorNode = OtherPlainError {}.into();
+    assert!(error.is_kind(TaggedErrorNodeKind::Variant1));
+    assert!(!error.is_kind(TaggedErrorNodeKind::Variant0));
+}
+
+#[test]
+fn implicit_discriminant_tags() {
+    let error: ImplicitTaggedErrorNode = OtherPlainError {}.into();
+    assert_eq!(error.tag(), 11i16);
+}
+
+#[test]
+fn kind_from_dyn_error() {
+    let error = OtherPlainError {};
+    let erased: &(dyn Error + 'static) = &error;
+    assert_eq!(TaggedErrorNode::from_dyn(erased), Some(TaggedErrorNodeKind::Variant1));
+    let node: TaggedErrorNode = PlainError {}.into();
+    assert_eq!(TaggedErrorNode::from_dyn(node.source().unwrap()), Some(TaggedErrorNodeKind::Variant0));
+    assert_eq!(TaggedErrorNode::from_dyn(&node), None);
+}
+
+#[error_leaf(message = format!("disk full"))]
+struct DiskFullError {}
+
+#[error_leaf(message = format!("permission denied"))]
+struct PermissionDeniedError {}
+
+#[error_leaf(message = format!("unexpected token"))]
+struct UnexpectedTokenError {}
+
+error_node! { type StorageErrorNode<DiskFullError, PermissionDeniedError> = "storage" }
+
+error_node! { type ParseErrorNode<UnexpectedTokenError> = "parse" }
+
+error_node! {
+    type AppErrorNode<+StorageErrorNode(DiskFullError, PermissionDeniedError), +ParseErrorNode(UnexpectedTokenError)> = "app"
+}
+
+fn write_file() -> Result<(), PermissionDeniedError> {
+    Err(PermissionDeniedError {})
+}
+
+fn parse_file() -> Result<(), UnexpectedTokenError> {
+    Err(UnexpectedTokenError {})
+}
+
+fn run(parse: bool) -> Result<(), AppErrorNode> {
+    if parse {
+        parse_file()?;
+    }
+    write_file()?;
+    Ok(())
+}
+
+#[test]
+fn lifted_leaves_convert_through_subnodes() {
+    let error = run(false).unwrap_err();
+    assert!(matches!(error, AppErrorNode::Variant0(StorageErrorNode::Variant1(_))));
+    assert_eq!(error.to_string(), "app: storage: permission denied");
+    let error = run(true).unwrap_err();
+    assert!(matches!(error, AppErrorNode::Variant1(ParseErrorNode::Variant0(_))));
+    assert_eq!(error.to_string(), "app: parse: unexpected token");
+}
+
+error_node! { type ToolErrorNode<#[lift(DiskFullError, PermissionDeniedError)] StorageErrorNode> = "tool" }
+
+fn save() -> Result<(), ToolErrorNode> {
+    write_file()?;
+    Ok(())
+}
+
+#[test]
+fn lift_attribute_converts_through_subnode() {
+    let error = save().unwrap_err();
+    assert!(matches!(error, ToolErrorNode::Variant0(StorageErrorNode::Variant1(_))));
+    assert_eq!(error.to_string(), "tool: storage: permission denied");
+}
+
+error_node! { type WorkspaceErrorNode<+ToolErrorNode(DiskFullError, PermissionDeniedError)> = "workspace" }
+
+fn sync_workspace() -> Result<(), WorkspaceErrorNode> {
+    Err(DiskFullError {})?
+}
+
+#[test]
+fn lifted_leaves_convert_across_levels() {
+    let error = sync_workspace().unwrap_err();
+    assert!(matches!(error, WorkspaceErrorNode::Variant0(ToolErrorNode::Variant0(StorageErrorNode::Variant0(_)))));
+    assert_eq!(error.to_string(), "workspace: tool: storage: disk full");
+}
+
+error_node! { #[debug(pretty)] type PrettyDebugErrorNode<ValidationErrorNode, PlainError> = "pretty" }
+
+#[test]
+fn pretty_debug_honors_alternate_flag() {
+    let error: PrettyDebugErrorNode = ValidationErrorNode::from(OutOfRangeError { value: 5 }).into();
+    assert_eq!(
+        format!("{:?}", error),
+        "PrettyDebugErrorNode::Variant0(Variant0(OutOfRangeError { value: 5 }))"
+    );
+    assert_eq!(
+        format!("{:#?}", error),
+        "PrettyDebugErrorNode::Variant0(\n    Variant0(\n        OutOfRangeError {\n            value: 5,\n        },\n    ),\n)"
+    );
+}
+
+#[error_leaf(message = format!("read {} bytes", self.bytes))]
+struct ReadError {
+    bytes: usize,
+}
+
+impl ReadError {
+    fn is_fatal(&self) -> bool {
+        self.bytes == 0
+    }
+}
+
+error_node! { type GuardedErrorNode<#[guard(|v| v.is_fatal())] ReadError, PlainError> = "guarded" }
+
+#[test]
+fn guarded_constructor_accepts_matching_value() {
+    let error = GuardedErrorNode::try_from_variant0(ReadError { bytes: 0 }).unwrap();
+    assert_eq!(error.to_string(), "guarded: read 0 bytes");
+}
+
+#[test]
+fn guarded_constructor_returns_rejected_value() {
+    let value = GuardedErrorNode::try_from_variant0(ReadError { bytes: 12 }).unwrap_err();
+    assert_eq!(value.bytes, 12);
+}
+
+mod guarded_with_result_alias {
+    use super::{PlainError, ReadError};
+    use hierrorchy::error_node;
+
+    #[allow(dead_code)]
+    type Result<T> = std::result::Result<T, String>;
+
+    error_node! {
+        pub type NamedGuardedErrorNode<#[guard(|v| v.is_fatal())] EmptyRead = ReadError, #[guard(|_| true)] HTTPPlain = PlainError> = "guarded"
+    }
+}
+
+#[test]
+fn guarded_constructor_named_in_snake_case() {
+    use guarded_with_result_alias::NamedGuardedErrorNode;
+    let error = NamedGuardedErrorNode::try_from_empty_read(ReadError { bytes: 0 }).unwrap();
+    assert!(matches!(error, NamedGuardedErrorNode::EmptyRead(_)));
+    let error = NamedGuardedErrorNode::try_from_http_plain(PlainError {}).unwrap();
+    assert!(matches!(error, NamedGuardedErrorNode::HTTPPlain(_)));
+}
+
+#[test]
+fn sources_walk_the_chain() {
+    let error: RequestErrorNode = ValidationErrorNode::from(OutOfRangeError { value: 1 }).into();
+    let sources: Vec<&(dyn Error + 'static)> = error.sources().collect();
+    assert_eq!(sources.len(), 3);
+    assert!(sources[0].is::<RequestErrorNode>());
+    assert!(sources[1].is::<ValidationErrorNode>());
+    assert!(sources[2].is::<OutOfRangeError>());
+}
+
+error_node! {
+    type SuffixErrorNode<#[suffix(" (check input encoding)")] UnexpectedTokenError, PlainError> = "parse error"
+}
+
+#[test]
+fn suffix_only_on_annotated_variant() {
+    let error: SuffixErrorNode = UnexpectedTokenError {}.into();
+    assert_eq!(error.to_string(), "parse error: unexpected token (check input encoding)");
+    let error: SuffixErrorNode = PlainError {}.into();
+    assert_eq!(error.to_string(), "parse error: plain error");
+}
+
+#[error_leaf(message = format!("{}", self.message))]
+struct StringError {
+    message: String,
+}
+
+impl From<&str> for StringError {
+    fn from(value: &str) -> Self {
+        StringError { message: value.to_string() }
+    }
+}
+
+impl From<String> for StringError {
+    fn from(value: String) -> Self {
+        StringError { message: value }
+    }
+}
+
+error_node! { type AcceptingErrorNode<#[accepts(&str, String)] StringError, PlainError> = "accepting" }
+
+#[test]
+fn accepted_types_convert_into_variant() {
+    let error: AcceptingErrorNode = "from a str".into();
+    assert!(matches!(error, AcceptingErrorNode::Variant0(_)));
+    assert_eq!(error.to_string(), "accepting: from a str");
+    let error: AcceptingErrorNode = String::from("from a string").into();
+    assert_eq!(error.to_string(), "accepting: from a string");
+    let error: AcceptingErrorNode = StringError::from("from the leaf").into();
+    assert_eq!(error.to_string(), "accepting: from the leaf");
+}
+
+#[error_leaf(message = format!("invalid number: {}", self.cause))]
+struct InvalidNumberError {
+    cause: std::num::ParseIntError,
+}
+
+impl From<std::num::ParseIntError> for InvalidNumberError {
+    fn from(cause: std::num::ParseIntError) -> Self {
+        InvalidNumberError { cause }
+    }
+}
+
+error_node! { type SettingErrorNode<#[accepts(std::num::ParseIntError)] InvalidNumberError> = "setting" }
+
+fn parse_setting(value: &str) -> Result<u32, SettingErrorNode> {
+    Ok(value.parse::<u32>()?)
+}
+
+#[test]
+fn accepted_type_converts_with_question_mark() {
+    assert_eq!(parse_setting("42").unwrap(), 42);
+    let error = parse_setting("forty-two").unwrap_err();
+    assert!(matches!(error, SettingErrorNode::Variant0(_)));
+    assert_eq!(error.to_string(), "setting: invalid number: invalid digit found in string");
+}
+
+#[error_leaf(message = format!("timed out"), kind_str = "timeout")]
+struct TimeoutError {}
+
+#[error_leaf(message = format!("refused"), kind_str = "refused")]
+struct RefusedError {}
+
+error_node! { #[kind_str] type ConnectionErrorNode<TimeoutError, RefusedError> = "connection" }
+
+error_node! { #[kind_str] type NetworkErrorNode<ConnectionErrorNode> = "network" }
+
+#[test]
+fn kind_str_forwards_to_active_variant() {
+    let error: ConnectionErrorNode = RefusedError {}.into();
+    assert_eq!(error.kind_str(), "refused");
+    let error: NetworkErrorNode = ConnectionErrorNode::from(TimeoutError {}).into();
+    assert_eq!(error.kind_str(), "timeout");
+}
+
+error_node! { type AngleBracketErrorNode<DiskFullError, PermissionDeniedError> = "storage" }
+
+error_node! { BraceErrorNode = "storage" { DiskFullError, PermissionDeniedError } }
+
+error_node! { BraceWithoutPrefixErrorNode { DiskFullError, PermissionDeniedError, } }
+
+#[test]
+fn brace_form_is_equivalent_to_angle_bracket_form() {
+    let angle_bracket: AngleBracketErrorNode = PermissionDeniedError {}.into();
+    let brace: BraceErrorNode = PermissionDeniedError {}.into();
+    assert!(matches!(angle_bracket, AngleBracketErrorNode::Variant1(_)));
+    assert!(matches!(brace, BraceErrorNode::Variant1(_)));
+    assert_eq!(angle_bracket.to_string(), brace.to_string());
+    assert_eq!(format!("{:?}", angle_bracket), format!("{:?}", brace));
+    let brace: BraceWithoutPrefixErrorNode = DiskFullError {}.into();
+    assert_eq!(brace.to_string(), "BraceWithoutPrefixErrorNode: disk full");
+}
+
+error_node! { #[display(numbered)] type NumberedErrorNode<RequestErrorNode> = "outer" }
+
+error_node! { #[display(numbered)] type NumberedSingleLevelErrorNode<PlainError> = "single" }
+
+#[test]
+fn numbered_display_across_levels() {
+    let error: NumberedErrorNode = RequestErrorNode::from(ValidationErrorNode::from(OutOfRangeError { value: 9 })).into();
+    assert_eq!(
+        error.to_string(),
+        "[1] outer: [2] request rejected: [3] validation failed: [4] value 9 is out of range"
+    );
+    let error: NumberedSingleLevelErrorNode = PlainError {}.into();
+    assert_eq!(error.to_string(), "[1] single: [2] plain error");
+}
+
+error_node! { type PartialFromErrorNode<PlainError, #[no_from] PlainError, DiskFullError> = "partial" }
+
+#[test]
+fn no_from_variant_is_constructed_explicitly() {
+    let error: PartialFromErrorNode = PlainError {}.into();
+    assert!(matches!(error, PartialFromErrorNode::Variant0(_)));
+    let error: PartialFromErrorNode = DiskFullError {}.into();
+    assert!(matches!(error, PartialFromErrorNode::Variant2(_)));
+    let error = PartialFromErrorNode::Variant1(PlainError {});
+    assert_eq!(error.to_string(), "partial: plain error");
+}
+
+error_node! { type ManualFromErrorNode<DiskFullError, #[no_from] PlainError> = "manual" }
+
+impl From<PlainError> for ManualFromErrorNode {
+    fn from(_: PlainError) -> Self {
+        ManualFromErrorNode::Variant0(DiskFullError {})
+    }
+}
+
+#[test]
+fn no_from_variant_leaves_room_for_manual_from() {
+    let error: ManualFromErrorNode = PlainError {}.into();
+    assert!(matches!(error, ManualFromErrorNode::Variant0(_)));
+    let error = ManualFromErrorNode::Variant1(PlainError {});
+    assert_eq!(error.source().unwrap().to_string(), "plain error");
+}
+
+error_node! { #[doc(alias = "AliasedError")] type AliasedErrorNode<PlainError> = "aliased" }
+
+#[test]
+fn doc_alias_is_accepted() {
+    let error: AliasedErrorNode = PlainError {}.into();
+    assert_eq!(error.to_string(), "aliased: plain error");
+}
+
+#[error_leaf(message = format!("no response after {} s", self.seconds), hashable)]
+struct SlowResponseError {
+    seconds: u32,
+}
+
+#[error_leaf(message = format!("connection reset"), hashable)]
+struct ResetError {}
+
+error_node! { #[derive(PartialEq)] type ResponseError<SlowResponseError, ResetError> }
+
+#[test]
+fn equal_nodes() {
+    let first: ResponseError = SlowResponseError { seconds: 3 }.into();
+    let second: ResponseError = SlowResponseError { seconds: 3 }.into();
+    assert_eq!(first, second);
+}
+
+#[test]
+fn unequal_nodes() {
+    let slow: ResponseError = SlowResponseError { seconds: 3 }.into();
+    let slower: ResponseError = SlowResponseError { seconds: 5 }.into();
+    let reset: ResponseError = ResetError {}.into();
+    assert_ne!(slow, slower);
+    assert_ne!(slow, reset);
+}
+
+error_node! { #[derive(PartialEq, Eq, Hash)] type HashableResponseError<SlowResponseError, ResetError> }
+
+#[test]
+fn extra_derives_are_emitted_on_node() {
+    let errors: std::collections::HashSet<HashableResponseError> = [
+        SlowResponseError { seconds: 3 }.into(),
+        SlowResponseError { seconds: 3 }.into(),
+        ResetError {}.into(),
+    ]
+    .into();
+    assert_eq!(errors.len(), 2);
+}
+
+error_node! { #[display(transparent)] type TransparentValidationErrorNode<ValidationErrorNode> }
+
+error_node! { #[display(transparent)] #[source(collapse)] type CollapsedValidationErrorNode<ValidationErrorNode> }
+
+#[test]
+fn transparent_node_displays_wrapped_error() {
+    let error: TransparentValidationErrorNode = ValidationErrorNode::from(OutOfRangeError { value: 1 }).into();
+    assert_eq!(error.to_string(), "validation failed: value 1 is out of range");
+    assert_eq!(
+        error.chain_messages(),
+        vec![
+            "validation failed: value 1 is out of range",
+            "validation failed: value 1 is out of range",
+            "value 1 is out of range",
+        ]
+    );
+}
+
+#[test]
+fn collapsed_source_skips_wrapped_error() {
+    let error: CollapsedValidationErrorNode = ValidationErrorNode::from(OutOfRangeError { value: 1 }).into();
+    assert_eq!(error.to_string(), "validation failed: value 1 is out of range");
+    assert!(error.source().unwrap().is::<OutOfRangeError>());
+    assert_eq!(
+        error.chain_messages(),
+        vec!["validation failed: value 1 is out of range", "value 1 is out of range"]
+    );
+}
+
+#[error_leaf(message = format!("retry limit {} reached", self.limit))]
+#[derive(Clone)]
+struct RetryLimitError {
+    limit: u8,
+}
+
+error_node! { #[derive(Clone)] type RetryErrorNode<RetryLimitError> = "retry failed" }
+
+#[test]
+fn cloned_node_keeps_variant() {
+    let error: RetryErrorNode = RetryLimitError { limit: 3 }.into();
+    let cloned = error.clone();
+    assert_eq!(cloned.to_string(), "retry failed: retry limit 3 reached");
+}
+
+#[error_leaf(message = format!("quota exceeded"), kind_str = "quota")]
+struct QuotaError {}
+
+error_node! { #[log_line] #[kind_str] #[repr(u16)] type BillingErrorNode<QuotaError = 402> = "billing" }
+
+error_node! { #[log_line] type PlainBillingErrorNode<QuotaError> = "billing" }
+
+#[test]
+fn node_log_lines() {
+    let error: BillingErrorNode = QuotaError {}.into();
+    assert_eq!(error.to_log_line(), "quota|402|billing: quota exceeded");
+    let error: PlainBillingErrorNode = QuotaError {}.into();
+    assert_eq!(error.to_log_line(), "||billing: quota exceeded");
+}
+
+/// Errors of a module denying `missing_docs`.
+#[deny(missing_docs)]
+pub mod documented {
+    use hierrorchy::{error_leaf, error_node};
+
+    /// A record was not found.
+    #[error_leaf(message = format!("record not found"))]
+    pub struct RecordNotFoundError {}
+
+    error_node! {
+        /// Errors of the record store.
+        pub type RecordStoreErrorNode<RecordNotFoundError> = "record store"
+    }
+}
+
+#[test]
+fn doc_comments_are_attached() {
+    let error: documented::RecordStoreErrorNode = documented::RecordNotFoundError {}.into();
+    assert_eq!(error.to_string(), "record store: record not found");
+}
+
+#[derive(Debug)]
+struct ResponseWrapper {
+    status: u16,
+    error: std::io::Error,
+}
+
+impl ResponseWrapper {
+    fn inner(&self) -> &std::io::Error {
+        &self.error
+    }
+}
+
+error_node! { type GatewayErrorNode<#[source(.inner())] ResponseWrapper> = "gateway" }
+
+#[test]
+fn source_accessor_of_variant() {
+    let error: GatewayErrorNode = ResponseWrapper {
+        status: 502,
+        error: std::io::Error::new(std::io::ErrorKind::ConnectionAborted, "upstream aborted"),
+    }
+    .into();
+    assert_eq!(error.to_string(), "gateway: upstream aborted");
+    assert!(error.source().unwrap().is::<std::io::Error>());
+    assert_eq!(error.inner().to_string(), "upstream aborted");
+    let GatewayErrorNode::Variant0(wrapper) = error;
+    assert_eq!(wrapper.status, 502);
+}
+
+error_node! { type BracedErrorNode<PlainError> = "{{config}}" }
+
+#[test]
+fn literal_prefix_with_escaped_braces() {
+    let error: BracedErrorNode = PlainError {}.into();
+    assert_eq!(error.to_string(), "{config}: plain error");
+}
+
+error_nodes! {
+    prefix "app";
+    type AppValidationErrorNode<OutOfRangeError>;
+    type AppPlainErrorNode<PlainError> = "plain";
+}
+
+#[test]
+fn nodes_sharing_a_prefix() {
+    let error: AppValidationErrorNode = OutOfRangeError { value: 0 }.into();
+    assert_eq!(error.to_string(), "app: AppValidationErrorNode: value 0 is out of range");
+    let error: AppPlainErrorNode = PlainError {}.into();
+    assert_eq!(error.to_string(), "app: plain: plain error");
+}
+
+struct OpaqueHandle {
+    id: u32,
+}
+
+#[error_leaf(message = format!("handle {} is stale", self.handle.id), derive_debug = false)]
+struct StaleHandleError {
+    handle: OpaqueHandle,
+}
+
+impl std::fmt::Debug for StaleHandleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "StaleHandleError({})", self.handle.id)
+    }
+}
+
+error_node! { #[no_debug] type HandleErrorNode<StaleHandleError> = "handle" }
+
+impl std::fmt::Debug for HandleErrorNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HandleErrorNode::Variant0(err) => write!(f, "HandleErrorNode({:?})", err),
+        }
+    }
+}
+
+#[test]
+fn manual_debug_with_no_debug() {
+    let error: HandleErrorNode = StaleHandleError { handle: OpaqueHandle { id: 9 } }.into();
+    assert_eq!(format!("{:?}", error), "HandleErrorNode(StaleHandleError(9))");
+    assert_eq!(error.to_string(), "handle: handle 9 is stale");
+}
+
+#[error_leaf(message = format!("stale read"), severity = Warn)]
+struct StaleReadError {}
+
+#[error_leaf(message = format!("lost write"), severity = Fatal)]
+struct LostWriteError {}
+
+error_node! { #[severity] type ReplicationErrorNode<StaleReadError, LostWriteError> = "replication" }
+
+#[test]
+fn node_severity_forwards_to_variant() {
+    use hierrorchy::Severity;
+    let error: ReplicationErrorNode = StaleReadError {}.into();
+    assert_eq!(error.severity(), Severity::Warn);
+    assert!(!error.is_at_least(Severity::Error));
+    let error: ReplicationErrorNode = LostWriteError {}.into();
+    assert_eq!(error.severity(), Severity::Fatal);
+    assert!(error.is_at_least(Severity::Fatal));
+}
+
+#[error_leaf(message = format!("quorum lost"))]
+struct QuorumLostError {}
+
+error_node! { #[severity] type ClusterErrorNode<ReplicationErrorNode, QuorumLostError, std::io::Error> = "cluster" }
+
+#[test]
+fn node_severity_defaults_to_error() {
+    use hierrorchy::{HasSeverity, Severity};
+    let error: ClusterErrorNode = ReplicationErrorNode::from(StaleReadError {}).into();
+    assert_eq!(error.severity(), Severity::Warn);
+    let error: ClusterErrorNode = QuorumLostError {}.into();
+    assert_eq!(error.severity(), Severity::Error);
+    let error: ClusterErrorNode = std::io::Error::other("connection reset").into();
+    assert_eq!(HasSeverity::severity(&error), Severity::Error);
+    assert!(error.is_at_least(Severity::Error));
+}
+
+#[error_leaf(message = format!("invalid header '{}'", self.name))]
+#[derive(Clone)]
+struct InvalidHeaderError {
+    name: String,
+}
+
+error_node! { type HeaderErrorNode<#[owned] InvalidHeaderError> = "headers" }
+
+#[test]
+fn borrowed_leaf_into_owned_variant() {
+    let leaf = InvalidHeaderError { name: String::from("X-Trace") };
+    let error: HeaderErrorNode = (&leaf).into();
+    assert_eq!(error.to_string(), "headers: invalid header 'X-Trace'");
+    // The borrowed leaf is still available.
+    assert_eq!(leaf.name, "X-Trace");
+    let error: HeaderErrorNode = leaf.into();
+    assert!(error.source().unwrap().is::<InvalidHeaderError>());
+}
+
+#[error_leaf(message = format!("snapshot of {} bytes is corrupted", self.bytes.len()))]
+struct CorruptedSnapshotError {
+    bytes: Vec<u8>,
+}
+
+error_node! { #[derive(Clone)] type SnapshotErrorNode<#[arc] CorruptedSnapshotError, RetryLimitError> = "snapshot" }
+
+#[test]
+fn cloned_arc_variant_shares_error() {
+    let error: SnapshotErrorNode = CorruptedSnapshotError { bytes: vec![0; 1024] }.into();
+    let cloned = error.clone();
+    assert_eq!(cloned.to_string(), "snapshot: snapshot of 1024 bytes is corrupted");
+    assert!(cloned.source().unwrap().is::<CorruptedSnapshotError>());
+    match (&error, &cloned) {
+        (SnapshotErrorNode::Variant0(original), SnapshotErrorNode::Variant0(copy)) => {
+            assert!(std::sync::Arc::ptr_eq(original, copy))
+        }
+        _ => panic!("the clone has a different variant"),
+    }
+}
+
+#[test]
+fn detailed_node_lists_chain() {
+    let error: BillingErrorNode = QuotaError {}.into();
+    assert_eq!(error.to_string(), "billing: quota exceeded");
+    assert_eq!(
+        error.detailed(),
+        "BillingErrorNode::Variant0 [kind: quota, code: 402]: billing: quota exceeded\n  caused by: quota exceeded"
+    );
+    let error: PlainBillingErrorNode = QuotaError {}.into();
+    assert_eq!(
+        error.detailed(),
+        "PlainBillingErrorNode::Variant0: billing: quota exceeded\n  caused by: quota exceeded"
+    );
+}
+
+#[error_leaf(message = format!("invalid token '{}'", self.token))]
+struct InvalidCredentialError {
+    token: String,
+}
+
+error_node! {
+    type AuthErrorNode<#[display(|_, f| f.write_str("authentication failed: <redacted>"))] InvalidCredentialError, QuotaError> = "authentication failed"
+}
+
+#[test]
+fn redacted_variant_hides_source() {
+    let error: AuthErrorNode = InvalidCredentialError { token: String::from("s3cr3t") }.into();
+    assert_eq!(error.to_string(), "authentication failed: <redacted>");
+    assert!(error.source().is_some());
+    let error: AuthErrorNode = QuotaError {}.into();
+    assert_eq!(error.to_string(), "authentication failed: quota exceeded");
+}
+
+error_node! { #[erased] pub type ErasedStorageErrorNode<DiskFullError, PermissionDeniedError> = "storage" }
+
+#[test]
+fn erased_node_keeps_kind() {
+    let error: ErasedStorageErrorNode = PermissionDeniedError {}.into();
+    assert_eq!(error.kind(), ErasedStorageErrorNodeKind::Variant1);
+    assert!(error.is_kind(ErasedStorageErrorNodeKind::Variant1));
+    assert_eq!(error.to_string(), "storage: permission denied");
+    assert!(error.source().unwrap().is::<PermissionDeniedError>());
+    assert_eq!(
+        std::mem::size_of::<ErasedStorageErrorNode>(),
+        std::mem::size_of::<(ErasedStorageErrorNodeKind, Box<dyn Error + Send + Sync>)>()
+    );
+    assert!(error.into_inner().is::<PermissionDeniedError>());
+}
+
+static CACHED_WALKS: AtomicUsize = AtomicUsize::new(0);
+static UNCACHED_WALKS: AtomicUsize = AtomicUsize::new(0);
+
+/// Counts the walks reaching the end of a source chain, as a leaf is the last error of the chain.
+fn count_walk(walks: &AtomicUsize) -> Option<&'static (dyn Error + 'static)> {
+    walks.fetch_add(1, Ordering::SeqCst);
+    None
+}
+
+#[error_leaf(message = format!("bad sector"), source_fn = count_walk(&CACHED_WALKS))]
+struct CachedBadSectorError {}
+
+#[error_leaf(message = format!("bad sector"), source_fn = count_walk(&UNCACHED_WALKS))]
+struct UncachedBadSectorError {}
+
+error_node! { type CachedDiskErrorNode<CachedBadSectorError> = "disk" }
+error_node! { type UncachedDiskErrorNode<UncachedBadSectorError> = "disk" }
+error_node! { #[erased] #[cache_root_cause] type CachedVolumeErrorNode<CachedDiskErrorNode> = "volume" }
+error_node! { #[erased] type UncachedVolumeErrorNode<UncachedDiskErrorNode> = "volume" }
+
+#[test]
+fn cached_root_cause_walks_chain_once() {
+    let error: CachedVolumeErrorNode = CachedDiskErrorNode::from(CachedBadSectorError {}).into();
+    for _ in 0..3 {
+        assert!(error.root_cause().is::<CachedBadSectorError>());
+    }
+    assert_eq!(CACHED_WALKS.load(Ordering::SeqCst), 1);
+    assert_eq!(error.to_string(), "volume: disk: bad sector");
+    assert!(error.into_inner().is::<CachedDiskErrorNode>());
+
+    let error: UncachedVolumeErrorNode = UncachedDiskErrorNode::from(UncachedBadSectorError {}).into();
+    for _ in 0..3 {
+        assert!(error.root_cause().is::<UncachedBadSectorError>());
+    }
+    assert_eq!(UNCACHED_WALKS.load(Ordering::SeqCst), 3);
+}
+
+error_node! { type IoErrorNode<std::io::Error> = "io" }
+
+#[test]
+fn node_lists_variant_types() {
+    assert_eq!(StorageErrorNode::VARIANT_TYPES, &["DiskFullError", "PermissionDeniedError"]);
+    assert_eq!(ErasedStorageErrorNode::VARIANT_TYPES, &["DiskFullError", "PermissionDeniedError"]);
+    assert_eq!(IoErrorNode::VARIANT_TYPES, &["std::io::Error"]);
+    assert_eq!(
+        BoundaryErrorNode::VARIANT_TYPES,
+        &["DiskFullError", "Box<dyn std::error::Error + Send + Sync>"]
+    );
+    assert_eq!(DomainErrorNode::VARIANT_TYPES, &["Box<dyn DomainError>", "PlainError"]);
+    assert_eq!(
+        BoxedOverriddenErrorNode::VARIANT_TYPES,
+        &["Box<dyn DomainError>", "Box<dyn DomainError + Send>", "Box<dyn std::error::Error + Send + Sync>"]
+    );
+}
+
+error_node! { #[display(raw_prefix)] type HttpErrorNode<QuotaError> = "http:" }
+
+#[test]
+fn raw_prefix_is_not_followed_by_colon() {
+    let error: HttpErrorNode = QuotaError {}.into();
+    assert_eq!(error.to_string(), "http: quota exceeded");
+}
+
+error_node! { type RouterErrorNode<QuotaError> = "router" }
+error_node! { #[display(separator = " — ")] type ProxyErrorNode<QuotaError> = "proxy" }
+error_node! { #[display(separator = " {} ", numbered)] type BalancerErrorNode<QuotaError> = "balancer" }
+
+#[test]
+fn separator_defaults_unless_overridden() {
+    let error: RouterErrorNode = QuotaError {}.into();
+    assert_eq!(error.to_string(), "router: quota exceeded");
+    let error: ProxyErrorNode = QuotaError {}.into();
+    assert_eq!(error.to_string(), "proxy — quota exceeded");
+    let error: BalancerErrorNode = QuotaError {}.into();
+    assert_eq!(error.to_string(), "[1] balancer {} [2] quota exceeded");
+}
+
+#[error_leaf(message = format!("sensor {} is offline", self.sensor))]
+struct SensorOfflineError {
+    sensor: u8,
+}
+
+error_node! { #[derive(PartialOrd, Ord)] type AlertErrorNode<SensorOfflineError, QuotaError, RetryLimitError> = "alert" }
+
+#[test]
+fn nodes_sorted_by_variant() {
+    let mut errors: Vec<AlertErrorNode> = vec![
+        RetryLimitError { limit: 2 }.into(),
+        SensorOfflineError { sensor: 4 }.into(),
+        QuotaError {}.into(),
+        SensorOfflineError { sensor: 1 }.into(),
+    ];
+    errors.sort();
+    let messages: Vec<String> = errors.iter().map(ToString::to_string).collect();
+    assert_eq!(
+        messages,
+        vec![
+            "alert: sensor 4 is offline",
+            "alert: sensor 1 is offline",
+            "alert: quota exceeded",
+            "alert: retry limit 2 reached",
+        ]
+    );
+    assert_eq!(
+        AlertErrorNode::from(SensorOfflineError { sensor: 1 }),
+        AlertErrorNode::from(SensorOfflineError { sensor: 2 })
+    );
+}
+
+fn localize(key: &str) -> String {
+    match key {
+        "err.quota" => String::from("quota dépassé"),
+        _ => String::from(key),
+    }
+}
+
+error_node! {
+    type LocalizedErrorNode<#[prefix(localize("err.quota"))] QuotaError, #[prefix("réseau")] TimeoutError, RefusedError> = "erreur"
+}
+
+#[test]
+fn variant_prefixes_replace_node_prefix() {
+    let error: LocalizedErrorNode = QuotaError {}.into();
+    assert_eq!(error.to_string(), "quota dépassé: quota exceeded");
+    let error: LocalizedErrorNode = TimeoutError {}.into();
+    assert_eq!(error.to_string(), "réseau: timed out");
+    let error: LocalizedErrorNode = RefusedError {}.into();
+    assert_eq!(error.to_string(), "erreur: refused");
+}
+
+error_node! { #[from_infallible] type ConversionErrorNode<InvalidNumberError> = "conversion" }
+
+fn convert<T, U>(value: T) -> Result<U, ConversionErrorNode>
+where
+    U: TryFrom<T>,
+    ConversionErrorNode: From<U::Error>,
+{
+    Ok(U::try_from(value)?)
+}
+
+fn parse_and_widen(value: &str) -> Result<u64, ConversionErrorNode> {
+    let number: u32 = value.parse().map_err(InvalidNumberError::from)?;
+    convert(number)
+}
+
+#[test]
+fn infallible_conversions_use_question_mark() {
+    assert_eq!(convert::<u8, u16>(7).unwrap(), 7);
+    assert_eq!(parse_and_widen("12").unwrap(), 12);
+    assert_eq!(
+        parse_and_widen("twelve").unwrap_err().to_string(),
+        "conversion: invalid number: invalid digit found in string"
+    );
+}
+
+#[test]
+fn root_cause_is_deepest_leaf() {
+    let error: NetworkErrorNode = ConnectionErrorNode::from(RefusedError {}).into();
+    assert!(error.root_cause().is::<RefusedError>());
+    assert_eq!(error.root_cause().to_string(), "refused");
+    let error: ConnectionErrorNode = TimeoutError {}.into();
+    assert!(error.root_cause().is::<TimeoutError>());
+}
+
+#[error_leaf(message = format!("unknown state"))]
+#[derive(Default)]
+struct UnknownStateError {}
+
+error_node! {
+    #[derive(Default)]
+    #[default = Variant1]
+    type StateErrorNode<SensorOfflineError, UnknownStateError> = "state machine"
+}
+
+#[test]
+fn default_node_builds_default_variant() {
+    let error = StateErrorNode::default();
+    assert!(matches!(error, StateErrorNode::Variant1(_)));
+    assert_eq!(error.to_string(), "state machine: unknown state");
+}
+
+error_node! { #[unique_prefix] type UniqueStorageErrorNode<DiskFullError> = "unique storage" }
+error_node! { #[unique_prefix] type UniqueDiskErrorNode<DiskFullError> = "unique_storage" }
+
+mod unique_prefix_scope {
+    use hierrorchy::error_node;
+
+    error_node! { #[unique_prefix] pub type UniqueStorageErrorNode<super::DiskFullError> = "unique storage" }
+}
+
+#[test]
+fn unique_prefixes_are_scoped_to_module() {
+    let error: UniqueStorageErrorNode = DiskFullError {}.into();
+    assert_eq!(error.to_string(), "unique storage: disk full");
+    let error: UniqueDiskErrorNode = DiskFullError {}.into();
+    assert_eq!(error.to_string(), "unique_storage: disk full");
+    let error: unique_prefix_scope::UniqueStorageErrorNode = DiskFullError {}.into();
+    assert_eq!(error.to_string(), "unique storage: disk full");
+}
+
+error_node! { #[backtrace] type TracedErrorNode<DiskFullError> = "traced" }
+
+fn fill_disk() -> Result<(), TracedErrorNode> {
+    Err(DiskFullError {})?
+}
+
+#[test]
+fn backtrace_is_captured_on_conversion() {
+    // The variable is read on the first capture of the process, which only happens in this test.
+    std::env::set_var("RUST_LIB_BACKTRACE", "1");
+    let error = fill_disk().unwrap_err();
+    assert_eq!(error.to_string(), "traced: disk full");
+    assert_eq!(error.backtrace().status(), std::backtrace::BacktraceStatus::Captured);
+    assert!(error.backtrace().to_string().contains("fill_disk"));
+}
+
+#[error_leaf(message = format!("connection reset"), retryable)]
+struct ConnectionResetError {}
+
+error_node! { type TransportErrorNode<ConnectionResetError, DiskFullError, std::io::Error> = "transport" }
+error_node! { type SyncErrorNode<TransportErrorNode, PermissionDeniedError> = "sync" }
+
+#[test]
+fn retryable_forwarded_by_nodes() {
+    let error: TransportErrorNode = ConnectionResetError {}.into();
+    assert!(error.is_retryable());
+    let error: TransportErrorNode = DiskFullError {}.into();
+    assert!(!error.is_retryable());
+    let error: TransportErrorNode = std::io::Error::other("broken pipe").into();
+    assert!(!error.is_retryable());
+    let error: SyncErrorNode = TransportErrorNode::from(ConnectionResetError {}).into();
+    assert!(error.is_retryable());
+    let error: SyncErrorNode = PermissionDeniedError {}.into();
+    assert!(!error.is_retryable());
+}
+
+#[error_leaf(message = format!("service unavailable"), code = 503)]
+struct ServiceUnavailableError {}
+
+error_node! { type UpstreamErrorNode<ServiceUnavailableError, DiskFullError> = "upstream" }
+
+#[test]
+fn node_parts_use_variant_code() {
+    let error: UpstreamErrorNode = ServiceUnavailableError {}.into();
+    assert_eq!(error.as_parts(), (503, String::from("upstream: service unavailable")));
+    let error: UpstreamErrorNode = DiskFullError {}.into();
+    assert_eq!(error.as_parts(), (0, String::from("upstream: disk full")));
+}
+
+error_node! { #[with_context(&'static str)] type ContextErrorNode<DiskFullError, std::io::Error> = "context" }
+
+fn store_chunk() -> Result<(), ContextErrorNode> {
+    Err(std::io::Error::other("broken pipe")).map_err(|err| ("chunk 3", err))?
+}
+
+#[test]
+fn context_tuple_converts_into_variant() {
+    let error: ContextErrorNode = ("journal", DiskFullError {}).into();
+    assert!(matches!(error, ContextErrorNode::Variant0(_, "journal")));
+    assert_eq!(error.context(), &"journal");
+    assert_eq!(error.to_string(), "context: disk full");
+    let error = store_chunk().unwrap_err();
+    assert!(matches!(error, ContextErrorNode::Variant1(_, "chunk 3")));
+    assert_eq!(error.to_string(), "context: broken pipe");
+}
+
+error_node! { type CappedErrorNode<#[terminal] TransportErrorNode, DiskFullError> = "capped" }
+
+#[test]
+fn terminal_variant_has_no_source() {
+    let error: CappedErrorNode = TransportErrorNode::from(ConnectionResetError {}).into();
+    assert!(error.source().is_none());
+    assert_eq!(error.to_string(), "capped: transport: connection reset");
+    assert_eq!(hierrorchy::sources(&error).count(), 1);
+    let error: CappedErrorNode = DiskFullError {}.into();
+    assert_eq!(error.source().unwrap().to_string(), "disk full");
+}
+
+error_node! { #[display(numbered)] type NumberedCappedErrorNode<#[terminal] TransportErrorNode, DiskFullError> = "capped" }
+
+#[test]
+fn numbered_chain_stops_at_terminal_variant() {
+    let error: NumberedCappedErrorNode = TransportErrorNode::from(ConnectionResetError {}).into();
+    assert_eq!(error.to_string(), "[1] capped: [2] transport: connection reset");
+    let error: NumberedCappedErrorNode = DiskFullError {}.into();
+    assert_eq!(error.to_string(), "[1] capped: [2] disk full");
+}
+
+error_node! { #[repr(u8)] type NamedErrorNode<Disk = DiskFullError, Io = std::io::Error = 7, PermissionDeniedError> = "named" }
+
+#[test]
+fn named_variants_are_used_in_enum() {
+    let error: NamedErrorNode = DiskFullError {}.into();
+    assert!(matches!(error, NamedErrorNode::Disk(_)));
+    assert_eq!(error.source().unwrap().to_string(), "disk full");
+    let error: NamedErrorNode = std::io::Error::other("broken pipe").into();
+    assert!(matches!(error, NamedErrorNode::Io(_)));
+    assert_eq!(error.kind(), NamedErrorNodeKind::Io);
+    assert_eq!(error.tag(), 7);
+    let error: NamedErrorNode = PermissionDeniedError {}.into();
+    assert!(matches!(error, NamedErrorNode::Variant2(_)));
+}
+
+error_node! { type QualifiedErrorNode<::std::io::Error, std::fmt::Error> = "qualified" }
+error_node! { QualifiedBraceErrorNode = "qualified" { ::std::fmt::Error } }
+
+#[test]
+fn qualified_paths_are_variants() {
+    let error: QualifiedErrorNode = std::io::Error::other("broken pipe").into();
+    assert!(error.source().unwrap().is::<std::io::Error>());
+    assert_eq!(error.to_string(), "qualified: broken pipe");
+    let error: QualifiedErrorNode = std::fmt::Error.into();
+    assert!(matches!(error, QualifiedErrorNode::Variant1(_)));
+    let error: QualifiedBraceErrorNode = std::fmt::Error.into();
+    assert!(error.source().unwrap().is::<std::fmt::Error>());
+}
+
+error_node! { #[result] type ResultErrorNode<DiskFullError> = "result" }
+error_node! { #[result] type GenericResultErrorNode<E: Error><WrapError<E>, DiskFullError> = "generic result" }
+
+fn flush() -> ResultErrorNodeResult<u8> {
+    Err(DiskFullError {})?
+}
+
+#[test]
+fn result_alias_uses_node_as_error() {
+    let result: Result<u8, ResultErrorNode> = flush();
+    assert_eq!(result.unwrap_err().to_string(), "result: disk full");
+    let result: GenericResultErrorNodeResult<(), std::fmt::Error> = Err(WrapError { inner: std::fmt::Error }.into());
+    assert!(matches!(result, Err(GenericResultErrorNode::Variant0(_))));
+}
+
+error_node! { type BoundaryErrorNode<DiskFullError, ..> = "boundary" }
+error_node! { BoundaryBraceErrorNode { DiskFullError, #[prefix("unexpected")] .. } }
+
+#[test]
+fn catch_all_variant_absorbs_boxed_errors() {
+    let error: BoundaryErrorNode = DiskFullError {}.into();
+    assert!(matches!(error, BoundaryErrorNode::Variant0(_)));
+    let boxed: Box<dyn Error + Send + Sync> = Box::new(std::fmt::Error);
+    let error: BoundaryErrorNode = boxed.into();
+    assert!(matches!(error, BoundaryErrorNode::Other(_)));
+    assert!(error.source().unwrap().is::<std::fmt::Error>());
+    assert_eq!(error.to_string(), "boundary: an error occurred when formatting an argument");
+    let error: BoundaryBraceErrorNode = Box::<dyn Error + Send + Sync>::from("lost connection").into();
+    assert_eq!(error.to_string(), "unexpected: lost connection");
+}
+
+error_node! {
+    type OverriddenErrorNode<
+        std::io::Error: "I/O failed: {}",
+        #[suffix(" (retrying)")] std::fmt::Error: "formatting failure",
+        DiskFullError,
+    > = "storage"
+}
+
+#[test]
+fn variant_message_overrides_prefix() {
+    let error: OverriddenErrorNode = std::io::Error::other("broken pipe").into();
+    assert_eq!(error.to_string(), "I/O failed: broken pipe");
+    assert!(error.source().unwrap().is::<std::io::Error>());
+    let error: OverriddenErrorNode = std::fmt::Error.into();
+    assert_eq!(error.to_string(), "formatting failure (retrying)");
+    let error: OverriddenErrorNode = DiskFullError {}.into();
+    assert_eq!(error.to_string(), "storage: disk full");
+}
+
+error_node! {
+    type BoxedOverriddenErrorNode<
+        Box<dyn DomainError>: "domain failed: {}",
+        #[display(|err, f| write!(f, "code {}", err.code()))] Box<dyn DomainError + Send>,
+        #[display(|err, f| write!(f, "unexpected: {}", err))] ..
+    > = "boxed"
+}
+
+#[test]
+fn boxed_variant_overrides() {
+    let error: BoxedOverriddenErrorNode = (Box::new(QuotaExceededError {}) as Box<dyn DomainError>).into();
+    assert_eq!(error.to_string(), "domain failed: quota exceeded");
+    let error: BoxedOverriddenErrorNode = (Box::new(QuotaExceededError {}) as Box<dyn DomainError + Send>).into();
+    assert_eq!(error.to_string(), "code 429");
+    let error: BoxedOverriddenErrorNode = Box::<dyn Error + Send + Sync>::from("lost connection").into();
+    assert_eq!(error.to_string(), "unexpected: lost connection");
+}
+
+/// Errors of a module which does not import `std::error::Error`.
+mod without_error_trait {
+    use hierrorchy::{error_leaf, error_node};
+
+    #[error_leaf("row {row} is corrupted", kind_str = "corrupted_row", code = 500, log_line)]
+    pub struct CorruptedRowError {
+        row: usize,
+    }
+
+    #[error_leaf(transparent)]
+    pub struct ForeignIoError(std::io::Error);
+
+    #[error_leaf(message = format!("checksum mismatch"), precompute, retryable)]
+    pub struct ChecksumError {}
+
+    error_node! { type TableErrorNode<CorruptedRowError, #[terminal] ForeignIoError, ..> = "table" }
+    error_node! { #[display(numbered)] #[debug(pretty)] type DatabaseErrorNode<+TableErrorNode(CorruptedRowError), ChecksumError> = "database" }
+    error_node! { #[display(transparent)] #[source(collapse)] type TransparentTableErrorNode<TableErrorNode> }
+    error_node! { #[erased] #[cache_root_cause] type ErasedDatabaseErrorNode<DatabaseErrorNode> = "erased" }
+    error_node! { #[backtrace] #[result] type BacktracedErrorNode<ChecksumError: "checksum: {}"> = "traced" }
+    error_node! { #[with_context(u32)] type ContextTableErrorNode<CorruptedRowError> = "context" }
+
+    #[test]
+    fn node_expansion_does_not_need_error_trait() {
+        let error: DatabaseErrorNode = CorruptedRowError { row: 3 }.into();
+        assert_eq!(error.to_string(), "[1] database: [2] table: [3] row 3 is corrupted");
+        assert_eq!(error.chain_messages().len(), 3);
+        let error: ErasedDatabaseErrorNode = error.into();
+        assert_eq!(error.root_cause().to_string(), "row 3 is corrupted");
+        let error: TableErrorNode = ForeignIoError(std::io::Error::other("broken pipe")).into();
+        assert_eq!(TransparentTableErrorNode::from(error).to_string(), "table: broken pipe");
+        let error: BacktracedErrorNode = ChecksumError::new().into();
+        let result: BacktracedErrorNodeResult<()> = Err(error);
+        assert!(matches!(result, Err(error) if error.to_string() == "checksum: checksum mismatch"));
+        let error: ContextTableErrorNode = (7, CorruptedRowError { row: 1 }).into();
+        assert_eq!(*error.context(), 7);
+    }
+}
+
+mod restricted_visibility {
+    pub mod storage {
+        use hierrorchy::{error_leaf, error_node};
+
+        #[error_leaf(message = format!("volume is read-only"))]
+        pub struct ReadOnlyVolumeError {}
+
+        error_node! { pub(crate) type VolumeErrorNode<ReadOnlyVolumeError> = "volume" }
+        error_node! { #[erased] pub(in crate::restricted_visibility) type ErasedVolumeErrorNode<ReadOnlyVolumeError> = "erased volume" }
+        error_node! { #[result] pub(super) MountErrorNode = "mount" { VolumeErrorNode } }
+    }
+
+    #[test]
+    fn restricted_visibilities_are_kept() {
+        let error: storage::VolumeErrorNode = storage::ReadOnlyVolumeError {}.into();
+        let result: storage::MountErrorNodeResult<()> = Err(error.into());
+        assert!(matches!(result, Err(storage::MountErrorNode::Variant0(_))));
+        let error: storage::ErasedVolumeErrorNode = storage::ReadOnlyVolumeError {}.into();
+        assert_eq!(error.kind(), storage::ErasedVolumeErrorNodeKind::Variant0);
+    }
+}
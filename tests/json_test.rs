@@ -0,0 +1,40 @@
+#![cfg(feature = "serde_json")]
+
+use hierrorchy::{error_leaf, error_node, ToJson};
+use serde_json::json;
+
+#[error_leaf(message = format!("disk is full"))]
+struct DiskFullError {}
+
+error_node! { type DiskErrorNode<DiskFullError> = "disk" }
+
+error_node! { type StorageErrorNode<DiskErrorNode, Io = std::io::Error> = "storage" }
+
+#[test]
+fn json_nests_node_sources() {
+    let error: StorageErrorNode = DiskErrorNode::from(DiskFullError {}).into();
+    assert_eq!(
+        error.to_json(),
+        json!({
+            "type": "StorageErrorNode",
+            "variant": "Variant0",
+            "message": "storage: disk: disk is full",
+            "source": {
+                "type": "DiskErrorNode",
+                "variant": "Variant0",
+                "message": "disk: disk is full",
+                "source": { "message": "disk is full" },
+            },
+        })
+    );
+    let error: StorageErrorNode = std::io::Error::other("broken pipe").into();
+    assert_eq!(
+        ToJson::to_json(&error),
+        json!({
+            "type": "StorageErrorNode",
+            "variant": "Io",
+            "message": "storage: broken pipe",
+            "source": { "message": "broken pipe" },
+        })
+    );
+}
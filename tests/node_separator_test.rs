@@ -0,0 +1,64 @@
+use hierrorchy::{error_leaf, error_node, error_nodes, ErrorNode};
+
+const DEFAULT_NODE_SEP: &str = " — ";
+
+#[error_leaf(message = format!("quota exceeded"))]
+struct QuotaError {}
+
+error_node! { type RouterErrorNode<QuotaError> = "router" }
+error_node! { #[display(separator = " / ")] type ProxyErrorNode<QuotaError> = "proxy" }
+error_node! { #[display(raw_prefix)] type HttpErrorNode<QuotaError> = "http:" }
+error_node! { #[display(numbered)] type BalancerErrorNode<RouterErrorNode> = "balancer" }
+
+error_nodes! {
+    prefix "gateway";
+    type GatewayRouteErrorNode<QuotaError> = "route";
+    type GatewayAuthErrorNode<#[prefix(String::from("token"))] QuotaError> = format!("auth");
+    #[unique_prefix] type GatewayLimitErrorNode<QuotaError> = "limit";
+    type GatewayErrorNode<QuotaError>;
+}
+
+#[derive(Debug, ErrorNode)]
+#[error_node(prefix = "config")]
+enum ConfigError {
+    Quota(QuotaError),
+}
+
+mod nested {
+    use super::QuotaError;
+    use hierrorchy::error_node;
+
+    error_node! { pub type NestedErrorNode<QuotaError> = "nested" }
+}
+
+#[test]
+fn crate_separator_replaces_default() {
+    let error: RouterErrorNode = QuotaError {}.into();
+    assert_eq!(error.to_string(), "router — quota exceeded");
+    let error: nested::NestedErrorNode = QuotaError {}.into();
+    assert_eq!(error.to_string(), "nested — quota exceeded");
+    let error: ConfigError = QuotaError {}.into();
+    assert_eq!(error.to_string(), "config — quota exceeded");
+    let error: BalancerErrorNode = RouterErrorNode::from(QuotaError {}).into();
+    assert_eq!(error.to_string(), "[1] balancer — [2] router — [3] quota exceeded");
+}
+
+#[test]
+fn node_separator_overrides_crate_separator() {
+    let error: ProxyErrorNode = QuotaError {}.into();
+    assert_eq!(error.to_string(), "proxy / quota exceeded");
+    let error: HttpErrorNode = QuotaError {}.into();
+    assert_eq!(error.to_string(), "http: quota exceeded");
+}
+
+#[test]
+fn crate_separator_joins_common_prefix() {
+    let error: GatewayRouteErrorNode = QuotaError {}.into();
+    assert_eq!(error.to_string(), "gateway — route — quota exceeded");
+    let error: GatewayAuthErrorNode = QuotaError {}.into();
+    assert_eq!(error.to_string(), "gateway — token — quota exceeded");
+    let error: GatewayLimitErrorNode = QuotaError {}.into();
+    assert_eq!(error.to_string(), "gateway — limit — quota exceeded");
+    let error: GatewayErrorNode = QuotaError {}.into();
+    assert_eq!(error.to_string(), "gateway — GatewayErrorNode — quota exceeded");
+}
@@ -0,0 +1,66 @@
+#![cfg(hierrorchy_provide)]
+#![feature(error_generic_member_access)]
+
+use std::backtrace::Backtrace;
+use std::error::{request_ref, Error, Request};
+use std::fmt::{self, Display, Formatter};
+
+use hierrorchy::{error_leaf, error_node};
+
+#[derive(Debug)]
+struct DeviceError {
+    backtrace: Backtrace,
+}
+
+impl Display for DeviceError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("device not ready")
+    }
+}
+
+impl Error for DeviceError {
+    fn provide<'a>(&'a self, request: &mut Request<'a>) {
+        request.provide_ref::<Backtrace>(&self.backtrace);
+    }
+}
+
+#[error_leaf(message = format!("cannot read block {}", self.block))]
+struct ReadBlockError {
+    block: u64,
+    #[source]
+    cause: DeviceError,
+}
+
+#[error_leaf(message = format!("cannot write block"), source_fn = self.device())]
+struct WriteBlockError {
+    cause: Option<DeviceError>,
+}
+
+impl WriteBlockError {
+    fn device(&self) -> Option<&(dyn Error + 'static)> {
+        self.cause.as_ref().map(|it| it as &(dyn Error + 'static))
+    }
+}
+
+#[test]
+fn leaf_forwards_provide_to_source() {
+    let error = ReadBlockError { block: 7, cause: DeviceError { backtrace: Backtrace::force_capture() } };
+    let backtrace = request_ref::<Backtrace>(&error).unwrap();
+    assert!(std::ptr::eq(backtrace, &error.cause.backtrace));
+    let error = WriteBlockError { cause: Some(DeviceError { backtrace: Backtrace::force_capture() }) };
+    assert!(request_ref::<Backtrace>(&error).is_some());
+    let error = WriteBlockError { cause: None };
+    assert!(request_ref::<Backtrace>(&error).is_none());
+}
+
+#[error_leaf(message = format!("checksum mismatch"))]
+struct ChecksumError {}
+
+error_node! { #[backtrace] type VerifyErrorNode<ChecksumError> = "verify" }
+
+#[test]
+fn backtrace_node_provides_backtrace() {
+    let error: VerifyErrorNode = ChecksumError {}.into();
+    let backtrace = request_ref::<Backtrace>(&error).unwrap();
+    assert!(std::ptr::eq(backtrace, error.backtrace()));
+}